@@ -0,0 +1,189 @@
+use lettre::message::{header::ContentType, Mailbox};        // For building the reminder message
+use lettre::transport::smtp::authentication::Credentials;   // For logging in to the SMTP server
+use lettre::transport::smtp::client::{Tls, TlsParameters};  // For encrypting the connection
+use lettre::{Message, SmtpTransport, Transport};            // For sending it
+
+// How the connection to the SMTP server is secured, from VIBE_SMTP_TLS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Security {
+    StartTls, // Upgrade a plain connection, failing if the server can't (default, port 587)
+    Tls,      // Encrypted from the start (port 465)
+    None,     // Plain text, e.g. for a local Postfix or msmtp relay (port 25)
+}
+
+impl Security {
+    fn default_port(self) -> u16 {
+        match self {
+            Security::StartTls => 587,
+            Security::Tls => 465,
+            Security::None => 25,
+        }
+    }
+}
+
+// EmailConfig is where email reminders go, read from VIBE_SMTP_HOST, VIBE_SMTP_TLS
+// (starttls, tls or none), VIBE_SMTP_PORT (default per VIBE_SMTP_TLS), VIBE_SMTP_USER and
+// VIBE_SMTP_PASSWORD, VIBE_SMTP_FROM (an address or "Name <address>") and VIBE_SMTP_TO
+// (comma-separated).
+#[derive(Debug)]
+pub struct EmailConfig {
+    host: String,
+    port: u16,
+    security: Security,
+    credentials: Option<(String, String)>, // User and password, when the server wants a login
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl EmailConfig {
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    // Reads the settings through `var`, so they can come from somewhere other than the environment
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let var = |name: &str| var(name).map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+        let host = var("VIBE_SMTP_HOST").ok_or("Set VIBE_SMTP_HOST to send email reminders")?;
+        let to = var("VIBE_SMTP_TO")
+            .ok_or("Set VIBE_SMTP_TO to send email reminders")?
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(|address| mailbox("VIBE_SMTP_TO", address))
+            .collect::<Result<Vec<_>, _>>()?;
+        let Some(first) = to.first() else {
+            return Err("VIBE_SMTP_TO has no addresses".to_string());
+        };
+        let from = match var("VIBE_SMTP_FROM") {
+            Some(from) => mailbox("VIBE_SMTP_FROM", &from)?,
+            None => first.clone(),
+        };
+
+        let security = match var("VIBE_SMTP_TLS").map(|tls| tls.to_lowercase()).as_deref() {
+            None | Some("starttls") => Security::StartTls,
+            Some("tls") => Security::Tls,
+            Some("none") => Security::None,
+            Some(other) => return Err(format!("Invalid VIBE_SMTP_TLS '{}' (expected starttls, tls or none)", other)),
+        };
+        let port = match var("VIBE_SMTP_PORT") {
+            Some(port) => port.parse().map_err(|_| format!("Invalid VIBE_SMTP_PORT '{}'", port))?,
+            None => security.default_port(),
+        };
+
+        let credentials = match (var("VIBE_SMTP_USER"), var("VIBE_SMTP_PASSWORD")) {
+            (Some(user), Some(password)) => Some((user, password)),
+            (None, None) => None,
+            _ => return Err("Set both VIBE_SMTP_USER and VIBE_SMTP_PASSWORD to log in".to_string()),
+        };
+        // Never send a password over a connection that isn't encrypted
+        if credentials.is_some() && security == Security::None {
+            return Err("VIBE_SMTP_TLS=none can't be used with VIBE_SMTP_USER and VIBE_SMTP_PASSWORD".to_string());
+        }
+
+        Ok(EmailConfig { host, port, security, credentials, from, to })
+    }
+
+    // Builds one short plain-text message; lettre encodes non-ASCII headers and the body
+    fn message(&self, subject: &str, body: &str) -> Result<Message, String> {
+        let mut builder = Message::builder().from(self.from.clone()).subject(subject).header(ContentType::TEXT_PLAIN);
+        for address in &self.to {
+            builder = builder.to(address.clone());
+        }
+        builder.body(body.to_string()).map_err(|e| format!("Could not build email: {}", e))
+    }
+
+    // Sends one short plain-text message, reporting why the server refused it
+    pub fn send(&self, subject: &str, body: &str) -> Result<(), String> {
+        let message = self.message(subject, body)?;
+        let tls = || TlsParameters::new(self.host.clone()).map_err(|e| format!("Could not set up TLS: {}", e));
+        let mut transport = SmtpTransport::builder_dangerous(&self.host).port(self.port).tls(match self.security {
+            Security::StartTls => Tls::Required(tls()?),
+            Security::Tls => Tls::Wrapper(tls()?),
+            Security::None => Tls::None,
+        });
+        if let Some((user, password)) = &self.credentials {
+            transport = transport.credentials(Credentials::new(user.clone(), password.clone()));
+        }
+        transport
+            .build()
+            .send(&message)
+            .map(|_| ())
+            .map_err(|e| format!("Could not send email via {}:{}: {}", self.host, self.port, e))
+    }
+}
+
+// Parses an address or "Name <address>" from the given setting
+fn mailbox(setting: &str, address: &str) -> Result<Mailbox, String> {
+    address.parse().map_err(|e| format!("Invalid address '{}' in {}: {}", address, setting, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Config from the given settings instead of the environment
+    fn config(vars: &[(&str, &str)]) -> Result<EmailConfig, String> {
+        EmailConfig::from_vars(|name| vars.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string()))
+    }
+
+    #[test]
+    fn defaults_to_starttls_on_587_and_the_first_recipient_as_sender() {
+        let config = config(&[("VIBE_SMTP_HOST", "mail.example.com"), ("VIBE_SMTP_TO", " a@example.com, ,b@example.com")]).unwrap();
+        assert_eq!(config.security, Security::StartTls);
+        assert_eq!(config.port, 587);
+        assert_eq!(config.to.len(), 2);
+        assert_eq!(config.from.email.to_string(), "a@example.com");
+    }
+
+    #[test]
+    fn port_follows_the_security_mode_unless_set() {
+        let tls = config(&[("VIBE_SMTP_HOST", "h"), ("VIBE_SMTP_TO", "a@example.com"), ("VIBE_SMTP_TLS", "TLS")]).unwrap();
+        assert_eq!((tls.security, tls.port), (Security::Tls, 465));
+        let plain = config(&[("VIBE_SMTP_HOST", "h"), ("VIBE_SMTP_TO", "a@example.com"), ("VIBE_SMTP_TLS", "none")]).unwrap();
+        assert_eq!((plain.security, plain.port), (Security::None, 25));
+        let custom = config(&[("VIBE_SMTP_HOST", "h"), ("VIBE_SMTP_TO", "a@example.com"), ("VIBE_SMTP_PORT", "2525")]).unwrap();
+        assert_eq!(custom.port, 2525);
+    }
+
+    #[test]
+    fn rejects_unusable_settings() {
+        let invalid = [
+            vec![("VIBE_SMTP_TO", "a@example.com")],
+            vec![("VIBE_SMTP_HOST", "h")],
+            vec![("VIBE_SMTP_HOST", "h"), ("VIBE_SMTP_TO", ", ,")],
+            vec![("VIBE_SMTP_HOST", "h"), ("VIBE_SMTP_TO", "not an address")],
+            vec![("VIBE_SMTP_HOST", "h"), ("VIBE_SMTP_TO", "a@example.com"), ("VIBE_SMTP_PORT", "99999")],
+            vec![("VIBE_SMTP_HOST", "h"), ("VIBE_SMTP_TO", "a@example.com"), ("VIBE_SMTP_TLS", "ssl")],
+            vec![("VIBE_SMTP_HOST", "h"), ("VIBE_SMTP_TO", "a@example.com"), ("VIBE_SMTP_USER", "me")],
+            vec![
+                ("VIBE_SMTP_HOST", "h"),
+                ("VIBE_SMTP_TO", "a@example.com"),
+                ("VIBE_SMTP_USER", "me"),
+                ("VIBE_SMTP_PASSWORD", "secret"),
+                ("VIBE_SMTP_TLS", "none"),
+            ],
+        ];
+        for vars in invalid {
+            assert!(config(&vars).is_err(), "{:?} should be rejected", vars);
+        }
+    }
+
+    #[test]
+    fn message_headers_encode_non_ascii_text() {
+        let config = config(&[
+            ("VIBE_SMTP_HOST", "h"),
+            ("VIBE_SMTP_TO", "a@example.com,b@example.com"),
+            ("VIBE_SMTP_FROM", "Jörg Tasks <jt@example.com>"),
+        ])
+        .unwrap();
+        let message = config.message("🚨 URGENT TASK DUE!", "Task 'Überweisung' is due now!").unwrap();
+        let text = String::from_utf8(message.formatted()).unwrap();
+        let headers = text.split("\r\n\r\n").next().unwrap();
+
+        assert!(headers.is_ascii(), "headers should be ASCII: {}", headers);
+        assert!(headers.contains("Subject: =?utf-8?b?"));
+        assert!(headers.contains("<jt@example.com>"));
+        assert!(headers.contains("To: a@example.com, b@example.com"));
+        assert!(headers.contains("Content-Type: text/plain; charset=utf-8"));
+    }
+}
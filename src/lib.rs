@@ -0,0 +1,2745 @@
+use chrono::{DateTime, Datelike, Duration, Local, MappedLocalTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday}; // For date/time handling
+use serde::{Deserialize, Serialize};          // For JSON serialization
+use std::{fs, path::{Path, PathBuf}};         // For file system operations
+use std::collections::BTreeMap;                 // For templates kept in name order
+use std::time::SystemTime;                      // For noticing changes made by other commands
+use std::str::FromStr;                          // For parsing enums from names
+
+// Time of day used for due dates given without one, unless VIBE_DEFAULT_DUE_TIME says otherwise
+const END_OF_DAY: NaiveTime = NaiveTime::from_hms_opt(23, 59, 0).unwrap();
+
+// Category represents a task category with associated color and emoji
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Category {
+    pub name: String,
+    pub color: String,
+    pub emoji: String,
+}
+
+// TimeEntry represents a single time tracking session
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimeEntry {
+    pub start_time: DateTime<Local>,
+    pub end_time: Option<DateTime<Local>>,
+    pub duration: Option<Duration>,
+}
+
+impl TimeEntry {
+    // Whether the session started within the range; either end may be open
+    pub fn started_between(&self, since: Option<DateTime<Local>>, until: Option<DateTime<Local>>) -> bool {
+        since.is_none_or(|since| self.start_time >= since) && until.is_none_or(|until| self.start_time < until)
+    }
+}
+
+// Task struct represents a single task in the system
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Task {
+    pub id: usize,                                // Unique identifier for the task
+    pub title: String,                            // Task title
+    pub description: Option<String>,              // Optional task description
+    pub priority: Priority,                       // Task priority level
+    pub status: Status,                           // Current task status
+    pub due_date: Option<DateTime<Local>>,        // Optional due date
+    pub created_at: DateTime<Local>,              // Creation timestamp
+    pub categories: Vec<Category>,                // Task categories/tags
+    pub time_entries: Vec<TimeEntry>,             // Time tracking entries
+    pub current_time_entry: Option<TimeEntry>,    // Currently running time entry
+    #[serde(default, skip_serializing)]
+    pub last_notification: Option<DateTime<Local>>, // Last notification sent, kept in the notification file
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,           // How often the task repeats
+    #[serde(default)]
+    pub subtasks: Vec<Subtask>,                   // Checklist items
+    #[serde(default)]
+    pub paused: bool,                             // Time tracking paused, waiting to resume
+    #[serde(default)]
+    pub hourly_rate: Option<f64>,                 // Billing rate for tracked time
+    #[serde(default)]
+    pub next_occurrence: Option<usize>,           // Task spawned when this recurring task was completed
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Local>>,    // When the task was marked Done
+    #[serde(default)]
+    pub notify_before: Option<i64>,               // Hours before due to start reminding, overriding the default
+    #[serde(default)]
+    pub notes: Vec<Note>,                         // Timestamped progress notes, oldest first
+    #[serde(default)]
+    pub depends_on: Vec<usize>,                   // IDs of tasks that must be done before this one
+    #[serde(default)]
+    pub progress: u8,                             // Percent complete, 0-100
+    #[serde(default)]
+    pub assignee: Option<String>,                 // Who owns the task, free-form
+    #[serde(default)]
+    pub estimated_minutes: Option<u32>,           // How long the task was expected to take
+    #[serde(default)]
+    pub tags: Vec<String>,                        // Free-form labels, stored without the leading #
+    #[serde(default)]
+    pub time_goal_minutes: Option<u32>,           // Minutes to track on the task each week
+    #[serde(default)]
+    pub escalate_within: Option<i64>,             // Hours before due to raise the priority, overriding the default
+    #[serde(default)]
+    pub escalated_from: Option<Priority>,         // Priority before automatic escalation
+    #[serde(default)]
+    pub rank: usize,                              // Position in the hand-curated backlog order, from 1
+}
+
+impl Task {
+    // Creates a fresh Todo task; the id is assigned when it is inserted
+    pub fn new(title: String, priority: Priority) -> Self {
+        Task {
+            id: 0,
+            title,
+            description: None,
+            priority,
+            status: Status::Todo,
+            due_date: None,
+            created_at: Local::now(),
+            categories: Vec::new(),
+            time_entries: Vec::new(),
+            current_time_entry: None,
+            last_notification: None,
+            recurrence: None,
+            subtasks: Vec::new(),
+            paused: false,
+            hourly_rate: None,
+            next_occurrence: None,
+            completed_at: None,
+            notify_before: None,
+            notes: Vec::new(),
+            depends_on: Vec::new(),
+            progress: 0,
+            assignee: None,
+            estimated_minutes: None,
+            tags: Vec::new(),
+            time_goal_minutes: None,
+            escalate_within: None,
+            escalated_from: None,
+            rank: 0,
+        }
+    }
+
+    // A fresh Todo copy of the task's reusable fields: no id, due date, time, notes,
+    // dependencies or progress, and every subtask unchecked
+    pub fn as_template(&self) -> Task {
+        Task {
+            description: self.description.clone(),
+            categories: self.categories.clone(),
+            recurrence: self.recurrence.clone(),
+            subtasks: self.subtasks.iter().map(|s| Subtask { title: s.title.clone(), done: false }).collect(),
+            hourly_rate: self.hourly_rate,
+            notify_before: self.notify_before,
+            assignee: self.assignee.clone(),
+            estimated_minutes: self.estimated_minutes,
+            tags: self.tags.clone(),
+            time_goal_minutes: self.time_goal_minutes,
+            escalate_within: self.escalate_within,
+            ..Task::new(self.title.clone(), self.escalated_from.clone().unwrap_or_else(|| self.priority.clone()))
+        }
+    }
+
+    // Time left until the due date (negative once it has passed), if the task has one
+    pub fn time_until_due(&self, now: DateTime<Local>) -> Option<Duration> {
+        self.due_date.map(|due| due - now)
+    }
+
+    // Changes the status, keeping the completion time in step
+    pub fn set_status(&mut self, status: &Status) {
+        self.completed_at = match status {
+            Status::Done => self.completed_at.or_else(|| Some(Local::now())),
+            _ => None,
+        };
+        self.status = status.clone();
+    }
+
+    // Whether the task is unfinished and its due date has passed
+    pub fn is_overdue(&self, now: DateTime<Local>) -> bool {
+        self.status != Status::Done && self.time_until_due(now).is_some_and(|left| left < Duration::zero())
+    }
+
+    // Sums tracked time, including the running session, for sessions started in the range
+    pub fn tracked_between(&self, since: Option<DateTime<Local>>, until: Option<DateTime<Local>>) -> Duration {
+        let completed: Duration = self.time_entries
+            .iter()
+            .filter(|e| e.started_between(since, until))
+            .filter_map(|e| e.duration)
+            .sum();
+        let running = match &self.current_time_entry {
+            Some(current) if current.started_between(since, until) => Local::now() - current.start_time,
+            _ => Duration::zero(),
+        };
+        completed + running
+    }
+
+    // Time tracked so far this week (from Monday) against the weekly goal, with the goal
+    // met in percent, or None without a goal
+    pub fn goal_progress(&self, now: DateTime<Local>) -> Option<(Duration, u32, u32)> {
+        let goal = self.time_goal_minutes.filter(|m| *m > 0)?;
+        let tracked = self.tracked_between(Some(start_of_week(now.date_naive())), None);
+        let percent = tracked.num_seconds() as f64 / 60.0 / f64::from(goal) * 100.0;
+        Some((tracked, goal, percent.round() as u32))
+    }
+
+    // All time tracked on the task, including the running session
+    pub fn total_tracked(&self) -> Duration {
+        self.tracked_between(None, None)
+    }
+
+    // Drops repeated categories (keeping the first of each name) and sorts them by name
+    pub fn normalize_categories(&mut self) {
+        self.categories.sort_by(|a, b| a.name.cmp(&b.name));
+        self.categories.dedup_by(|a, b| a.name == b.name);
+    }
+
+    // How far tracked time is over (positive) or under (negative) the estimate, in percent
+    pub fn estimate_error_percent(&self) -> Option<f64> {
+        let estimate = f64::from(self.estimated_minutes.filter(|m| *m > 0)?);
+        let spent = self.total_tracked().num_seconds() as f64 / 60.0;
+        Some((spent - estimate) / estimate * 100.0)
+    }
+
+    // Closes the running time entry and records it, returning its duration
+    pub fn finish_current_entry(&mut self) -> Option<Duration> {
+        let mut current_entry = self.current_time_entry.take()?;
+        let end_time = Local::now();
+        let duration = end_time - current_entry.start_time;
+        current_entry.end_time = Some(end_time);
+        current_entry.duration = Some(duration);
+        self.time_entries.push(current_entry);
+        Some(duration)
+    }
+
+    // Corrects the start and end of a recorded session (index is 0-based)
+    pub fn adjust_time_entry(&mut self, index: usize, start_time: DateTime<Local>, end_time: DateTime<Local>) -> Result<(), TaskError> {
+        let Some(entry) = self.time_entries.get_mut(index) else {
+            return Err(TaskError::SessionNotFound { id: self.id, index: index + 1 });
+        };
+        if end_time < start_time {
+            return Err(TaskError::SessionEndsBeforeStart { start: start_time, end: end_time });
+        }
+
+        entry.start_time = start_time;
+        entry.end_time = Some(end_time);
+        entry.duration = Some(end_time - start_time);
+        Ok(())
+    }
+}
+
+// Subtask represents a single checklist item within a task
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Subtask {
+    pub title: String,
+    pub done: bool,
+}
+
+// Note is a timestamped entry in a task's progress log
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Note {
+    pub text: String,
+    pub created_at: DateTime<Local>,
+}
+
+// Recurrence defines how often a repeating task comes back after completion
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+    EveryDays(u32),
+}
+
+impl Recurrence {
+    // Time between one occurrence's due date and the next
+    pub fn interval(&self) -> Duration {
+        match self {
+            Recurrence::Daily => Duration::days(1),
+            Recurrence::Weekly => Duration::weeks(1),
+            Recurrence::EveryDays(days) => Duration::days(i64::from(*days)),
+        }
+    }
+}
+
+impl std::fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly => write!(f, "weekly"),
+            Recurrence::EveryDays(days) => write!(f, "every {} days", days),
+        }
+    }
+}
+
+// Priority enum defines possible priority levels for tasks, ordered Low < Urgent
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    // Every priority from lowest to highest, in the order prompts list them
+    pub const ALL: [Priority; 4] = [Priority::Low, Priority::Medium, Priority::High, Priority::Urgent];
+
+    // The next priority up, stopping at Urgent
+    pub fn raised(&self) -> Priority {
+        let index = Priority::ALL.iter().position(|p| p == self).unwrap_or(0);
+        Priority::ALL[(index + 1).min(Priority::ALL.len() - 1)].clone()
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Priority::Low => write!(f, "Low"),
+            Priority::Medium => write!(f, "Medium"),
+            Priority::High => write!(f, "High"),
+            Priority::Urgent => write!(f, "Urgent"),
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    // Parses a priority name as given on the command line or in the environment
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "medium" => Ok(Priority::Medium),
+            "high" => Ok(Priority::High),
+            "urgent" => Ok(Priority::Urgent),
+            _ => Err(format!(
+                "invalid priority '{}' (expected low, medium, high or urgent)",
+                input
+            )),
+        }
+    }
+}
+
+// Status enum defines possible states for a task
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Status {
+    Todo,
+    InProgress,
+    Done,
+}
+
+impl Status {
+    // Every status in workflow order, as prompts list them
+    pub const ALL: [Status; 3] = [Status::Todo, Status::InProgress, Status::Done];
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Status::Todo => write!(f, "Todo"),
+            Status::InProgress => write!(f, "In Progress"),
+            Status::Done => write!(f, "Done"),
+        }
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    // Parses a status name as given on the command line
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input.trim().to_lowercase().as_str() {
+            "todo" => Ok(Status::Todo),
+            "in-progress" | "in progress" => Ok(Status::InProgress),
+            "done" => Ok(Status::Done),
+            _ => Err(format!(
+                "invalid status '{}' (expected todo, in-progress or done)",
+                input
+            )),
+        }
+    }
+}
+
+// Parses a recurrence like "daily", "weekly", "3d", "2w" or "3" (days)
+pub fn parse_recurrence(input: &str) -> Result<Recurrence, String> {
+    let input = input.to_lowercase();
+    match input.as_str() {
+        "daily" => Ok(Recurrence::Daily),
+        "weekly" => Ok(Recurrence::Weekly),
+        _ => {
+            // Any whole number of days given as a duration works, so "2w" repeats every 14 days
+            let days = input.parse::<i64>().ok().or_else(|| {
+                parse_duration(&input)
+                    .ok()
+                    .filter(|d| *d == Duration::days(d.num_days()))
+                    .map(|d| d.num_days())
+            });
+            match days.and_then(|days| u32::try_from(days).ok()) {
+                Some(days) if days > 0 => Ok(Recurrence::EveryDays(days)),
+                _ => Err(format!(
+                    "invalid recurrence '{}' (expected daily, weekly or a number of days like 3d)",
+                    input
+                )),
+            }
+        }
+    }
+}
+
+// Parses a duration like "30m", "3h", "2d" or "1w"
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim().to_lowercase();
+    let error = || format!("invalid duration '{}' (expected a number followed by m, h, d or w, e.g. 3h)", input);
+    let unit = input.chars().last().ok_or_else(error)?;
+    let amount: i64 = input[..input.len() - unit.len_utf8()].parse().map_err(|_| error())?;
+
+    match unit {
+        'm' => Ok(Duration::minutes(amount)),
+        'h' => Ok(Duration::hours(amount)),
+        'd' => Ok(Duration::days(amount)),
+        'w' => Ok(Duration::weeks(amount)),
+        _ => Err(error()),
+    }
+}
+
+// Parses a calendar date in the "YYYY-MM-DD" format
+pub fn parse_date(input: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .map_err(|_| format!("invalid date '{}' (expected YYYY-MM-DD)", input))
+}
+
+// Parses a due date, either "YYYY-MM-DD HH:MM" or a relative form resolved against now
+pub fn parse_due_date(input: &str) -> Result<DateTime<Local>, String> {
+    parse_due_date_from(input, Local::now(), default_due_time())
+}
+
+// Time of day for due dates given as just a day: VIBE_DEFAULT_DUE_TIME as HH:MM, or 23:59
+// if it's unset or not a valid time
+pub fn default_due_time() -> NaiveTime {
+    std::env::var("VIBE_DEFAULT_DUE_TIME")
+        .ok()
+        .and_then(|time| NaiveTime::parse_from_str(time.trim(), "%H:%M").ok())
+        .unwrap_or(END_OF_DAY)
+}
+
+// Most categories a task may have: VIBE_MAX_CATEGORIES, or 3 if it's unset or not a
+// number. 0 (or less) means no limit.
+pub fn max_categories() -> usize {
+    std::env::var("VIBE_MAX_CATEGORIES")
+        .ok()
+        .and_then(|max| max.trim().parse::<i64>().ok())
+        .map_or(3, |max| usize::try_from(max).unwrap_or(0))
+}
+
+// Fails with TooManyCategories if a task with this many categories would be over the
+// max_categories() limit
+pub fn check_category_count(count: usize) -> Result<(), TaskError> {
+    match max_categories() {
+        max if max > 0 && count > max => Err(TaskError::TooManyCategories(max)),
+        _ => Ok(()),
+    }
+}
+
+// Parses a due date relative to `now`, trying these forms in order:
+//   "2024-06-01 14:00", "2024-06-01", "+3d" (any parse_duration unit), "in 2 hours",
+//   then "today", "tomorrow", "friday", "next monday", each optionally followed by "HH:MM".
+// Dates and day names without a time are due at `day_time`.
+pub fn parse_due_date_from(input: &str, now: DateTime<Local>, day_time: NaiveTime) -> Result<DateTime<Local>, String> {
+    let error = || {
+        format!(
+            "invalid due date '{}' (expected YYYY-MM-DD HH:MM, YYYY-MM-DD, tomorrow, today 17:00, +3d, next monday or in 2 hours)",
+            input
+        )
+    };
+    let input = input.trim().to_lowercase();
+
+    if let Ok(dt) = NaiveDateTime::parse_from_str(&input, "%Y-%m-%d %H:%M") {
+        return Ok(local_from_naive(dt));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+        return Ok(local_from_naive(date.and_time(day_time)));
+    }
+
+    // "+3d", "+2h", ...
+    if let Some(offset) = input.strip_prefix('+') {
+        return parse_duration(offset).map(|duration| now + duration).map_err(|_| error());
+    }
+
+    // "in 2 hours", "in 3 days", ...
+    if let Some(offset) = input.strip_prefix("in ") {
+        return parse_spelled_duration(offset).map(|duration| now + duration).ok_or_else(error);
+    }
+
+    // "<day> [HH:MM]"
+    let (day, time) = match input.rsplit_once(' ') {
+        Some((day, time)) if time.contains(':') => {
+            (day, NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| error())?)
+        }
+        _ => (input.as_str(), day_time),
+    };
+    let today = now.date_naive();
+    let date = match day {
+        "today" => today,
+        "tomorrow" => today + Duration::days(1),
+        _ => {
+            // Weekday names always mean the next such day after today
+            let weekday: Weekday = day.strip_prefix("next ").unwrap_or(day).parse().map_err(|_| error())?;
+            let days_ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            today + Duration::days(if days_ahead == 0 { 7 } else { i64::from(days_ahead) })
+        }
+    };
+    Ok(local_from_naive(date.and_time(time)))
+}
+
+// Parses an amount and a spelled-out unit such as "2 hours", "1 day" or "3 weeks", as used
+// by "in 2 hours" and "3 days ago"
+fn parse_spelled_duration(text: &str) -> Option<Duration> {
+    let (amount, unit) = text.split_once(' ')?;
+    let amount: i64 = amount.parse().ok()?;
+    match unit.trim_end_matches('s') {
+        "min" | "minute" => Some(Duration::minutes(amount)),
+        "hour" => Some(Duration::hours(amount)),
+        "day" => Some(Duration::days(amount)),
+        "week" => Some(Duration::weeks(amount)),
+        _ => None,
+    }
+}
+
+// Parses a point in the past, e.g. for "created after" filters
+pub fn parse_past_date(input: &str) -> Result<DateTime<Local>, String> {
+    parse_past_date_from(input, Local::now())
+}
+
+// Parses a point in the past relative to `now`. Dates mean the start of that day:
+//   "2024-06-01", "today", "yesterday", "monday"/"last monday" (the most recent one),
+//   "-3d" (any parse_duration unit) or "3 days ago". Anything else is read as a due date.
+pub fn parse_past_date_from(input: &str, now: DateTime<Local>) -> Result<DateTime<Local>, String> {
+    let error = || {
+        format!(
+            "invalid date '{}' (expected YYYY-MM-DD, YYYY-MM-DD HH:MM, today, yesterday, last monday, -7d or 3 days ago)",
+            input
+        )
+    };
+    let trimmed = input.trim().to_lowercase();
+    let today = now.date_naive();
+
+    if let Ok(date) = NaiveDate::parse_from_str(&trimmed, "%Y-%m-%d") {
+        return Ok(start_of_day(date));
+    }
+    match trimmed.as_str() {
+        "today" => return Ok(start_of_day(today)),
+        "yesterday" => return Ok(start_of_day(today - Duration::days(1))),
+        _ => {}
+    }
+
+    // "-3d", "-2h", ...
+    if let Some(offset) = trimmed.strip_prefix('-') {
+        return parse_duration(offset).map(|duration| now - duration).map_err(|_| error());
+    }
+
+    // "3 days ago", "2 weeks ago", ...
+    if let Some(offset) = trimmed.strip_suffix(" ago") {
+        return parse_spelled_duration(offset).map(|duration| now - duration).ok_or_else(error);
+    }
+
+    // Weekday names mean the most recent such day, today included
+    if let Ok(weekday) = trimmed.strip_prefix("last ").unwrap_or(&trimmed).parse::<Weekday>() {
+        let days_back = (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+        return Ok(start_of_day(today - Duration::days(i64::from(days_back))));
+    }
+
+    parse_due_date_from(input, now, END_OF_DAY).map_err(|_| error())
+}
+
+// Returns local midnight at the start of the given date
+pub fn start_of_day(date: NaiveDate) -> DateTime<Local> {
+    local_from_naive(date.and_hms_opt(0, 0, 0).unwrap())
+}
+
+// Turns an inclusive range of dates into start and end times for tracked_between
+pub fn date_range(since: Option<NaiveDate>, until: Option<NaiveDate>) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
+    (since.map(start_of_day), until.map(|date| start_of_day(date) + Duration::days(1)))
+}
+
+// Number of tasks with each status, in Status::ALL order
+pub fn count_by_status<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> [(Status, usize); 3] {
+    let mut counts = Status::ALL.map(|status| (status, 0));
+    for task in tasks {
+        if let Some((_, count)) = counts.iter_mut().find(|(status, _)| *status == task.status) {
+            *count += 1;
+        }
+    }
+    counts
+}
+
+// Returns local midnight at the start of the week (Monday) containing the given date
+pub fn start_of_week(date: NaiveDate) -> DateTime<Local> {
+    start_of_day(date - Duration::days(i64::from(date.weekday().num_days_from_monday())))
+}
+
+// Interprets a wall-clock time in the local time zone
+pub fn local_from_naive(naive: NaiveDateTime) -> DateTime<Local> {
+    wall_clock_time(&Local, naive)
+}
+
+// Interprets a wall-clock time in the given time zone. Times repeated when clocks go back
+// take the first occurrence. Times skipped when clocks go forward don't exist, so they
+// move forward by the gap (02:30 becomes 03:30).
+fn wall_clock_time<Tz: TimeZone>(tz: &Tz, naive: NaiveDateTime) -> DateTime<Tz> {
+    let first_occurrence = |naive: NaiveDateTime| match tz.from_local_datetime(&naive) {
+        MappedLocalTime::Single(time) => Some(time),
+        // chrono orders these by offset, not by when they happen
+        MappedLocalTime::Ambiguous(a, b) => Some(a.min(b)),
+        MappedLocalTime::None => None,
+    };
+    first_occurrence(naive)
+        .or_else(|| first_occurrence(naive + Duration::hours(1)))
+        .unwrap_or_else(|| tz.from_utc_datetime(&naive))
+}
+
+// Ranks how pressing a task is: priority sets the base, a nearing due date adds
+// urgency and anything overdue outranks the rest. Done tasks score zero.
+pub fn task_score(task: &Task, now: DateTime<Local>) -> f64 {
+    if task.status == Status::Done {
+        return 0.0;
+    }
+    let priority = match task.priority {
+        Priority::Low => 1.0,
+        Priority::Medium => 2.0,
+        Priority::High => 3.0,
+        Priority::Urgent => 4.0,
+    };
+    let urgency = match task.time_until_due(now) {
+        Some(left) if left < Duration::zero() => 10.0 + (-left).num_hours() as f64 / 24.0,
+        Some(left) => 5.0 / (1.0 + left.num_hours() as f64 / 24.0),
+        None => 0.0,
+    };
+    priority + urgency
+}
+
+// Formats tracked time as "1h 12m 30s", or as decimal hours like "1.21 hours" when asked
+pub fn format_tracked(duration: Duration, decimal_hours: bool) -> String {
+    let seconds = duration.num_seconds();
+    if decimal_hours {
+        return format!("{:.2} hours", seconds as f64 / 3600.0);
+    }
+    let (h, m, s) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if h > 0 {
+        format!("{}h {}m {}s", h, m, s)
+    } else if m > 0 {
+        format!("{}m {}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+// Draws a ten-segment bar like "[████░░░░░░] 40%"
+pub fn progress_bar(percent: u8) -> String {
+    let filled = usize::from(percent.min(100)) / 10;
+    format!("[{}{}] {}%", "█".repeat(filled), "░".repeat(10 - filled), percent)
+}
+
+// Strips the optional leading # from a tag
+pub fn normalize_tag(tag: &str) -> &str {
+    tag.trim().trim_start_matches('#')
+}
+
+// Formats task IDs as "#2, #5"
+pub fn format_task_ids(ids: &[usize]) -> String {
+    ids.iter().map(|id| format!("#{}", id)).collect::<Vec<_>>().join(", ")
+}
+
+// Formats a number of minutes as "14h 30m"
+pub fn format_minutes(minutes: u32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+// Describes a time difference compactly in its largest whole unit, e.g. "45s", "3h" or
+// "2w"; the sign is ignored so callers can say "in" or "ago"
+pub fn humanize(delta: Duration) -> String {
+    let seconds = delta.num_seconds().abs();
+    match seconds {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86_400 => format!("{}h", s / 3600),
+        s if s < 7 * 86_400 => format!("{}d", s / 86_400),
+        s => format!("{}w", s / (7 * 86_400)),
+    }
+}
+
+// Escapes text values for iCalendar (RFC 5545 section 3.3.11)
+fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Folds an iCalendar content line to at most 75 octets per line without splitting characters
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    folded
+}
+
+// Renders tasks as a Markdown checklist grouped by status
+pub fn render_markdown(tasks: &[&Task]) -> String {
+    let mut out = String::from("# Tasks\n");
+    for status in Status::ALL {
+        let group: Vec<&&Task> = tasks.iter().filter(|t| t.status == status).collect();
+        if group.is_empty() {
+            continue;
+        }
+
+        out.push_str(&format!("\n## {}\n\n", status));
+        for task in group {
+            let checkbox = if task.status == Status::Done { "[x]" } else { "[ ]" };
+            out.push_str(&format!("- {} **{}** ({})", checkbox, task.title, task.priority));
+            if let Some(due) = task.due_date {
+                out.push_str(&format!(" — due {}", due.format("%Y-%m-%d %H:%M")));
+            }
+            out.push('\n');
+            if let Some(desc) = &task.description {
+                out.push_str(&format!("  {}\n", desc));
+            }
+        }
+    }
+    out
+}
+
+// Renders tasks that have a due date as iCalendar events (CRLF line endings, folded lines)
+pub fn render_ics(tasks: &[&Task], reminder: Option<Duration>) -> String {
+    let now = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//vibe_tasks//vibe_tasks//EN".to_string(),
+    ];
+
+    for task in tasks {
+        // Tasks without a due date have nothing to put on a calendar
+        let Some(due) = task.due_date else { continue };
+        let due = due.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ");
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:vibe-tasks-{}@vibe_tasks", task.id));
+        lines.push(format!("DTSTAMP:{}", now));
+        lines.push(format!("DTSTART:{}", due));
+        lines.push(format!("SUMMARY:{}", escape_ics_text(&task.title)));
+        if let Some(desc) = &task.description {
+            lines.push(format!("DESCRIPTION:{}", escape_ics_text(desc)));
+        }
+        if let Some(reminder) = reminder {
+            lines.push("BEGIN:VALARM".to_string());
+            lines.push("ACTION:DISPLAY".to_string());
+            lines.push(format!("DESCRIPTION:{}", escape_ics_text(&task.title)));
+            lines.push(format!("TRIGGER:-PT{}M", reminder.num_minutes()));
+            lines.push("END:VALARM".to_string());
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_ics_line(line) + "\r\n").collect()
+}
+
+// TaskFilter narrows a task list down; unset fields match everything
+#[derive(Debug, Default, Clone)]
+pub struct TaskFilter {
+    pub status: Option<Status>,
+    pub priority: Option<Priority>,
+    pub assignee: Option<String>,                 // Matched ignoring case
+    pub tag: Option<String>,                      // Matched ignoring case and a leading #
+    pub created_after: Option<DateTime<Local>>,   // Inclusive
+    pub created_before: Option<DateTime<Local>>,  // Exclusive
+}
+
+impl TaskFilter {
+    // Whether the task passes every filter that is set
+    pub fn matches(&self, task: &Task) -> bool {
+        self.status.as_ref().is_none_or(|status| &task.status == status)
+            && self.priority.as_ref().is_none_or(|priority| &task.priority == priority)
+            && self.assignee.as_ref().is_none_or(|wanted| {
+                task.assignee.as_ref().is_some_and(|assignee| assignee.eq_ignore_ascii_case(wanted))
+            })
+            && self.tag.as_deref().is_none_or(|wanted| {
+                task.tags.iter().any(|tag| tag.eq_ignore_ascii_case(normalize_tag(wanted)))
+            })
+            && self.created_after.is_none_or(|after| task.created_at >= after)
+            && self.created_before.is_none_or(|before| task.created_at < before)
+    }
+}
+
+// Completion describes what marking a task Done did besides changing its status
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub stopped_timer: bool,                      // A running timer was stopped and recorded
+    pub next_occurrence: Option<usize>,           // Id of the task spawned for the next recurrence
+}
+
+// TaskError is why an operation on tasks failed
+#[derive(Debug)]
+pub enum TaskError {
+    NotFound(usize),                              // No task with this id
+    NotArchived(usize),                           // No archived task with this id
+    SubtaskNotFound { id: usize, index: usize },  // Checklist item (1-based) missing on a task
+    SessionNotFound { id: usize, index: usize },  // Time entry (1-based) missing on a task
+    SessionEndsBeforeStart { start: DateTime<Local>, end: DateTime<Local> },
+    TimerAlreadyRunning(usize),                   // The task's own timer is already running
+    TimerBusy { id: usize, title: String },       // Another task's timer is running
+    NoActiveTimer(usize),                         // Nothing to stop or pause
+    TimerNotPaused(usize),                        // Nothing to resume
+    AlreadyDone(usize),                           // Starting a timer on a finished task
+    Blocked { id: usize, blockers: Vec<usize> },  // Unfinished dependencies
+    SelfDependency,
+    DependencyCycle { id: usize, on: usize },     // `on` already depends on `id`
+    UnknownCategory { name: String, available: Vec<String> },
+    CategoryNotFound(String),                     // No category definition with this name
+    TooManyCategories(usize),                     // Over the per-task category limit
+    NoHomeDir,                                    // Nowhere to put the default tasks file
+    Corrupt { path: PathBuf, error: serde_json::Error }, // The file isn't valid task data
+    InvalidListName(String),                      // Not usable as a list file name
+    TemplateNotFound(String),                     // No saved template with this name
+    NoTitleMatch(String),                         // No task id or title matches the text
+    AmbiguousTitle { text: String, candidates: Vec<(usize, String)> }, // Several titles match the text
+    ChangedOnDisk(PathBuf),                       // Another command saved since this one loaded
+    Io(String),                                   // Reading or writing a file failed
+}
+
+impl std::fmt::Display for TaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TaskError::NotFound(id) => write!(f, "Task {} not found!", id),
+            TaskError::NotArchived(id) => write!(f, "Task {} not found in the archive!", id),
+            TaskError::SubtaskNotFound { id, index } => write!(f, "Subtask {} not found on task {}!", index, id),
+            TaskError::SessionNotFound { id, index } => write!(f, "Session {} not found on task {}!", index, id),
+            TaskError::SessionEndsBeforeStart { start, end } => write!(
+                f,
+                "End time {} is before start time {}",
+                end.format("%Y-%m-%d %H:%M:%S"),
+                start.format("%Y-%m-%d %H:%M:%S")
+            ),
+            TaskError::TimerAlreadyRunning(id) => write!(f, "Time tracking is already running for task {}!", id),
+            TaskError::TimerBusy { id, title } => write!(
+                f,
+                "Task #{} '{}' is already being tracked — stop it first (or use --switch)",
+                id, title
+            ),
+            TaskError::NoActiveTimer(id) => write!(f, "No active time tracking for task {}!", id),
+            TaskError::TimerNotPaused(id) => write!(f, "Time tracking isn't paused for task {}!", id),
+            TaskError::AlreadyDone(id) => write!(
+                f,
+                "Task #{} is already done — reopen it first? (use --force to start anyway)",
+                id
+            ),
+            TaskError::Blocked { id, blockers } => write!(
+                f,
+                "Task {} is blocked by {} — finish those first (or use --force)",
+                id,
+                format_task_ids(blockers)
+            ),
+            TaskError::SelfDependency => write!(f, "A task can't depend on itself!"),
+            TaskError::DependencyCycle { id, on } => write!(
+                f,
+                "Task {} already depends on task {} — that would create a cycle!",
+                on, id
+            ),
+            TaskError::UnknownCategory { name, available } => {
+                write!(f, "Unknown category '{}', available: {}", name, available.join(", "))
+            }
+            TaskError::CategoryNotFound(name) => write!(f, "Category '{}' not found!", name),
+            TaskError::TooManyCategories(max) => write!(
+                f,
+                "A task can have at most {} categories (set VIBE_MAX_CATEGORIES to change the limit)",
+                max
+            ),
+            TaskError::NoHomeDir => write!(f, "Could not find home directory"),
+            TaskError::InvalidListName(name) => write!(
+                f,
+                "Invalid list name '{}' — use letters, digits, '-' and '_' (config, templates and names ending in _archive are reserved)",
+                name
+            ),
+            TaskError::NoTitleMatch(text) => write!(f, "No task matches '{}'!", text),
+            TaskError::AmbiguousTitle { text, candidates } => {
+                let names: Vec<String> = candidates.iter().map(|(id, title)| format!("#{} '{}'", id, title)).collect();
+                write!(f, "'{}' matches {} tasks: {} — use the id or more of the title", text, names.len(), names.join(", "))
+            }
+            TaskError::TemplateNotFound(name) => write!(f, "Template '{}' not found!", name),
+            TaskError::Corrupt { path, error } => write!(f, "Could not read {}: {}", path.display(), error),
+            TaskError::ChangedOnDisk(path) => write!(
+                f,
+                "{} was changed by another command since it was loaded — run this command again to apply it to the latest tasks",
+                path.display()
+            ),
+            TaskError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for TaskError {}
+
+// Lets callers that report errors as plain text use `?` on task operations
+impl From<TaskError> for String {
+    fn from(error: TaskError) -> Self {
+        error.to_string()
+    }
+}
+
+// Name that refers to the original unnamed list, ~/.vibe_tasks.json
+pub const DEFAULT_LIST: &str = "default";
+
+// Config holds settings remembered between runs, stored in ~/.vibe_tasks/config.json
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub default_list: Option<String>,         // List used when --list isn't given, None for the unnamed list
+    #[serde(default)]
+    pub due_offsets: BTreeMap<Priority, String>, // Due date offset (e.g. "4h") for new tasks added without one
+}
+
+impl Config {
+    fn path() -> Result<PathBuf, TaskError> {
+        Ok(TaskManager::lists_dir()?.join("config.json"))
+    }
+
+    // Loads the config, or the defaults if it hasn't been written yet
+    pub fn load() -> Result<Self, TaskError> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let data = fs::read_to_string(&path)
+            .map_err(|e| TaskError::Io(format!("Could not read {}: {}", path.display(), e)))?;
+        serde_json::from_str(&data).map_err(|error| TaskError::Corrupt { path, error })
+    }
+
+    // How far from now a new task with this priority is due when no due date is given
+    pub fn due_offset(&self, priority: &Priority) -> Option<Duration> {
+        self.due_offsets.get(priority).and_then(|offset| parse_duration(offset).ok())
+    }
+
+    pub fn save(&self) -> Result<(), TaskError> {
+        let path = Self::path()?;
+        create_parent_dir(&path)?;
+        let json = serde_json::to_string_pretty(self).unwrap();
+        fs::write(&path, json).map_err(|e| TaskError::Io(format!("Could not write {}: {}", path.display(), e)))
+    }
+}
+
+// Templates are named task blueprints shared by every list, stored in ~/.vibe_tasks/templates.json
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Templates {
+    pub templates: BTreeMap<String, Task>,
+}
+
+impl Templates {
+    fn path() -> Result<PathBuf, TaskError> {
+        Ok(TaskManager::lists_dir()?.join("templates.json"))
+    }
+
+    // Loads the saved templates, or none if nothing has been saved yet
+    pub fn load() -> Result<Self, TaskError> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Templates::default());
+        }
+        let data = fs::read_to_string(&path)
+            .map_err(|e| TaskError::Io(format!("Could not read {}: {}", path.display(), e)))?;
+        serde_json::from_str(&data).map_err(|error| TaskError::Corrupt { path, error })
+    }
+
+    pub fn save(&self) -> Result<(), TaskError> {
+        let path = Self::path()?;
+        create_parent_dir(&path)?;
+        let json = serde_json::to_string_pretty(self).unwrap();
+        fs::write(&path, json).map_err(|e| TaskError::Io(format!("Could not write {}: {}", path.display(), e)))
+    }
+
+    // Stores a task's reusable fields under a name, returning whether it replaced an existing template
+    pub fn insert(&mut self, name: &str, task: &Task) -> bool {
+        self.templates.insert(name.to_string(), task.as_template()).is_some()
+    }
+
+    // A new task built from the named template, created now
+    pub fn instantiate(&self, name: &str) -> Result<Task, TaskError> {
+        self.templates
+            .get(name)
+            .map(Task::as_template)
+            .ok_or_else(|| TaskError::TemplateNotFound(name.to_string()))
+    }
+}
+
+// Makes sure the directory a file goes in exists, e.g. ~/.vibe_tasks/ for a new named list
+fn create_parent_dir(path: &Path) -> Result<(), TaskError> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => fs::create_dir_all(dir)
+            .map_err(|e| TaskError::Io(format!("Could not create {}: {}", dir.display(), e))),
+        _ => Ok(()),
+    }
+}
+
+// When a file was last written, or None if it doesn't exist
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+// TaskFile is the on-disk layout of the tasks file
+#[derive(Debug, Default, Deserialize)]
+struct TaskFile {
+    next_id: usize,                           // Id handed to the next new task
+    tasks: Vec<Task>,
+    #[serde(default)]
+    categories: Option<Vec<Category>>,        // User-defined categories, None until customized
+    #[serde(default)]
+    archive: Option<Vec<Task>>,               // Only in undo snapshots: the archive to restore as well
+}
+
+// TaskManager handles all task-related operations and storage. Operations change the
+// tasks in memory only; call save() to write them out. Archiving, purging and undo are
+// the exception, as they move tasks between files.
+pub struct TaskManager {
+    pub tasks: Vec<Task>,
+    pub categories: Vec<Category>,
+    next_id: usize,
+    file_path: PathBuf,
+    snapshot: String,                         // State as loaded, written out for undo on save
+    archive_snapshot: Option<Vec<Task>>,      // Archive before this run changed it, also kept for undo
+    modified: Option<SystemTime>,             // Tasks file mtime when loaded or last written, None if missing
+    notified: BTreeMap<usize, DateTime<Local>>, // Reminder times as loaded or last written
+}
+
+// The categories offered before the user defines their own
+fn default_categories() -> Vec<Category> {
+    [
+        ("Work", "blue", "💼"),
+        ("Personal", "green", "🏠"),
+        ("Study", "yellow", "📚"),
+        ("Health", "red", "💪"),
+        ("Shopping", "cyan", "🛒"),
+    ]
+    .iter()
+    .map(|(name, color, emoji)| Category {
+        name: name.to_string(),
+        color: color.to_string(),
+        emoji: emoji.to_string(),
+    })
+    .collect()
+}
+
+impl TaskManager {
+    // Returns the tasks file to use: an explicit path if given, then the named list, then
+    // the default list remembered in the config, otherwise ~/.vibe_tasks.json
+    pub fn resolve_file_path(file: Option<PathBuf>, list: Option<&str>) -> Result<PathBuf, TaskError> {
+        if let Some(path) = file {
+            return Ok(path);
+        }
+        match list {
+            Some(name) => Self::list_path(name),
+            None => match Config::load()?.default_list {
+                Some(name) => Self::list_path(&name),
+                None => Self::list_path(DEFAULT_LIST),
+            },
+        }
+    }
+
+    // Named lists are kept together in ~/.vibe_tasks/
+    pub fn lists_dir() -> Result<PathBuf, TaskError> {
+        let home_dir = dirs::home_dir().ok_or(TaskError::NoHomeDir)?;
+        Ok(home_dir.join(".vibe_tasks"))
+    }
+
+    // Returns the file for a named list, e.g. ~/.vibe_tasks/work.json
+    pub fn list_path(name: &str) -> Result<PathBuf, TaskError> {
+        if name == DEFAULT_LIST {
+            let home_dir = dirs::home_dir().ok_or(TaskError::NoHomeDir)?;
+            return Ok(home_dir.join(".vibe_tasks.json"));
+        }
+        // Names must not clash with the config, templates, archive or undo files kept alongside
+        let valid = !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            && name != "config"
+            && name != "templates"
+            && !name.ends_with("_archive");
+        if !valid {
+            return Err(TaskError::InvalidListName(name.to_string()));
+        }
+        Ok(Self::lists_dir()?.join(format!("{}.json", name)))
+    }
+
+    // Returns every list with its number of tasks, the unnamed default list first
+    pub fn lists() -> Result<Vec<(String, usize)>, TaskError> {
+        let mut names = Vec::new();
+        let dir = Self::lists_dir()?;
+        if dir.exists() {
+            let entries = fs::read_dir(&dir)
+                .map_err(|e| TaskError::Io(format!("Could not read {}: {}", dir.display(), e)))?;
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Some(name) = path.file_name().and_then(|n| n.to_str()).and_then(|n| n.strip_suffix(".json")) else {
+                    continue;
+                };
+                if Self::list_path(name).is_ok_and(|list_path| list_path == path) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        let current = Config::load()?.default_list;
+        if let Some(current) = current.filter(|name| !names.contains(name) && Self::list_path(name).is_ok()) {
+            names.push(current);
+        }
+        names.sort();
+        names.insert(0, DEFAULT_LIST.to_string());
+
+        names
+            .into_iter()
+            .map(|name| {
+                let count = TaskManager::new(Self::list_path(&name)?)?.tasks.len();
+                Ok((name, count))
+            })
+            .collect()
+    }
+
+    // Creates a new TaskManager instance, loading tasks from the given file (empty if it doesn't exist)
+    pub fn new(file_path: PathBuf) -> Result<Self, TaskError> {
+        // Taken before reading so a write in between is noticed on save
+        let modified = modified_time(&file_path);
+        let file = if file_path.exists() {
+            let data = fs::read_to_string(&file_path)
+                .map_err(|e| TaskError::Io(format!("Could not read {}: {}", file_path.display(), e)))?;
+            match serde_json::from_str::<TaskFile>(&data) {
+                Ok(file) => file,
+                // Older versions stored a bare array of tasks without an id counter
+                Err(e) => match serde_json::from_str::<Vec<Task>>(&data) {
+                    Ok(tasks) => TaskFile { tasks, ..TaskFile::default() },
+                    Err(_) => return Err(TaskError::Corrupt { path: file_path, error: e }),
+                },
+            }
+        } else {
+            TaskFile::default()
+        };
+
+        // Never hand out an id that is already in use, even if the counter was lost
+        let max_id = file.tasks.iter().map(|t| t.id).max().unwrap_or(0);
+        let next_id = file.next_id.max(max_id + 1);
+
+        let mut task_manager = TaskManager {
+            tasks: file.tasks,
+            categories: file.categories.unwrap_or_else(default_categories),
+            next_id,
+            file_path,
+            snapshot: String::new(),
+            archive_snapshot: None,
+            modified,
+            notified: BTreeMap::new(),
+        };
+        task_manager.load_notifications()?;
+        // Files from before ranks existed get them from the task order
+        task_manager.renumber_ranks();
+        // Remember the state before this run's changes so they can be undone
+        task_manager.snapshot = task_manager.to_json()?;
+        Ok(task_manager)
+    }
+
+    // The tasks file this manager reads from and saves to
+    pub fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+
+    // Serializes the current state in the tasks file layout
+    fn to_json(&self) -> Result<String, TaskError> {
+        let file = serde_json::json!({
+            "next_id": self.next_id,
+            "tasks": self.tasks,
+            "categories": self.categories,
+        });
+        serde_json::to_string_pretty(&file).map_err(|e| TaskError::Io(format!("Could not serialize tasks: {}", e)))
+    }
+
+    // Saves current tasks to the JSON file, keeping the pre-change state for undo. Refuses
+    // with ChangedOnDisk if another command wrote the file since it was loaded.
+    pub fn save(&mut self) -> Result<(), TaskError> {
+        self.check_unchanged()?;
+        self.save_overwriting()
+    }
+
+    // Fails with ChangedOnDisk if another command wrote the tasks file since it was loaded
+    pub fn check_unchanged(&self) -> Result<(), TaskError> {
+        if modified_time(&self.file_path) != self.modified {
+            return Err(TaskError::ChangedOnDisk(self.file_path.clone()));
+        }
+        Ok(())
+    }
+
+    // Lets the next save overwrite whatever another command wrote to the tasks file
+    pub fn ignore_changes_on_disk(&mut self) {
+        self.modified = modified_time(&self.file_path);
+    }
+
+    // Saves even if the tasks file changed on disk, discarding those changes
+    pub fn save_overwriting(&mut self) -> Result<(), TaskError> {
+        for task in &mut self.tasks {
+            task.normalize_categories();
+        }
+        self.renumber_ranks();
+        create_parent_dir(&self.file_path)?;
+        let undo_path = self.undo_path();
+        fs::write(&undo_path, self.undo_snapshot()?)
+            .map_err(|e| TaskError::Io(format!("Could not write {}: {}", undo_path.display(), e)))?;
+
+        let data = self.to_json()?;
+        fs::write(&self.file_path, data)
+            .map_err(|e| TaskError::Io(format!("Could not write {}: {}", self.file_path.display(), e)))?;
+        self.modified = modified_time(&self.file_path);
+        self.save_notifications()
+    }
+
+    // Reminder times live beside the tasks file, e.g. ~/.vibe_tasks.notify.json, so sending
+    // reminders doesn't rewrite the tasks themselves
+    fn notify_path(&self) -> PathBuf {
+        let stem = self.file_path.file_stem().unwrap_or_default().to_string_lossy();
+        self.file_path.with_file_name(format!("{}.notify.json", stem))
+    }
+
+    // Each task's last reminder time, keyed by id
+    fn notification_times(&self) -> BTreeMap<usize, DateTime<Local>> {
+        self.tasks
+            .iter()
+            .filter_map(|task| Some((task.id, task.last_notification?)))
+            .collect()
+    }
+
+    // Fills in reminder times from the notification file. Without one, the times stored in
+    // the tasks file by older versions are kept and moved over on the next save.
+    fn load_notifications(&mut self) -> Result<(), TaskError> {
+        let path = self.notify_path();
+        if !path.exists() {
+            return Ok(());
+        }
+        let data = fs::read_to_string(&path)
+            .map_err(|e| TaskError::Io(format!("Could not read {}: {}", path.display(), e)))?;
+        self.notified = serde_json::from_str(&data).map_err(|error| TaskError::Corrupt { path, error })?;
+        for task in &mut self.tasks {
+            task.last_notification = self.notified.get(&task.id).copied();
+        }
+        Ok(())
+    }
+
+    // Writes the notification file if any reminder time changed, leaving the tasks file alone
+    pub fn save_notifications(&mut self) -> Result<(), TaskError> {
+        let times = self.notification_times();
+        if times == self.notified {
+            return Ok(());
+        }
+        let path = self.notify_path();
+        create_parent_dir(&path)?;
+        let json = serde_json::to_string_pretty(&times).unwrap();
+        fs::write(&path, json).map_err(|e| TaskError::Io(format!("Could not write {}: {}", path.display(), e)))?;
+        self.notified = times;
+        Ok(())
+    }
+
+    // The state to return to on undo: the tasks as loaded, plus the archive as it was if
+    // this run moved tasks in or out of it
+    fn undo_snapshot(&self) -> Result<String, TaskError> {
+        let Some(archive) = &self.archive_snapshot else {
+            return Ok(self.snapshot.clone());
+        };
+        let mut snapshot: serde_json::Value = serde_json::from_str(&self.snapshot)
+            .map_err(|e| TaskError::Io(format!("Could not serialize tasks: {}", e)))?;
+        snapshot["archive"] = serde_json::json!(archive);
+        serde_json::to_string_pretty(&snapshot).map_err(|e| TaskError::Io(format!("Could not serialize tasks: {}", e)))
+    }
+
+    // The undo snapshot lives next to the tasks file, e.g. ~/.vibe_tasks.undo.json
+    fn undo_path(&self) -> PathBuf {
+        let stem = self.file_path.file_stem().unwrap_or_default().to_string_lossy();
+        self.file_path.with_file_name(format!("{}.undo.json", stem))
+    }
+
+    // Restores the tasks file from before the last command that changed tasks, returning
+    // a description of each change that was reverted (None if there is nothing to undo)
+    pub fn undo(&mut self) -> Result<Option<Vec<String>>, TaskError> {
+        let undo_path = self.undo_path();
+        if !undo_path.exists() {
+            return Ok(None);
+        }
+
+        let data = fs::read_to_string(&undo_path)
+            .map_err(|e| TaskError::Io(format!("Could not read {}: {}", undo_path.display(), e)))?;
+        let previous: TaskFile = serde_json::from_str(&data)
+            .map_err(|e| TaskError::Io(format!("Could not read {}: {}", undo_path.display(), e)))?;
+
+        let current_archive = match previous.archive {
+            Some(_) => self.load_archive()?,
+            None => Vec::new(),
+        };
+
+        // Describe what changed between the current and restored task lists
+        let mut messages = Vec::new();
+        for old in &previous.tasks {
+            match self.find(old.id) {
+                None if current_archive.iter().any(|t| t.id == old.id) => {
+                    messages.push(format!("Moved task #{} '{}' back out of the archive", old.id, old.title))
+                }
+                None => messages.push(format!("Restored task #{} '{}'", old.id, old.title)),
+                Some(current) if serde_json::to_value(current).ok() != serde_json::to_value(old).ok() => {
+                    messages.push(format!("Reverted changes to task #{} '{}'", old.id, old.title))
+                }
+                Some(_) => {}
+            }
+        }
+        for current in self.tasks.iter().filter(|t| !previous.tasks.iter().any(|old| old.id == t.id)) {
+            if previous.archive.iter().flatten().any(|t| t.id == current.id) {
+                messages.push(format!("Moved task #{} '{}' back to the archive", current.id, current.title));
+            } else {
+                messages.push(format!("Removed task #{} '{}'", current.id, current.title));
+            }
+        }
+
+        // Restore the archive first, so a failure leaves tasks duplicated rather than lost
+        let mut data = data;
+        if let Some(archive) = &previous.archive {
+            self.save_archive(archive)?;
+            let mut file: serde_json::Value = serde_json::from_str(&data)
+                .map_err(|e| TaskError::Io(format!("Could not read {}: {}", undo_path.display(), e)))?;
+            file.as_object_mut().map(|fields| fields.remove("archive"));
+            data = serde_json::to_string_pretty(&file)
+                .map_err(|e| TaskError::Io(format!("Could not serialize tasks: {}", e)))?;
+        }
+        fs::write(&self.file_path, data)
+            .map_err(|e| TaskError::Io(format!("Could not write {}: {}", self.file_path.display(), e)))?;
+        self.modified = modified_time(&self.file_path);
+        fs::remove_file(&undo_path)
+            .map_err(|e| TaskError::Io(format!("Could not remove {}: {}", undo_path.display(), e)))?;
+
+        if messages.is_empty() {
+            messages.push("Restored the previous state".to_string());
+        }
+        Ok(Some(messages))
+    }
+
+    // The archive lives next to the tasks file, e.g. ~/.vibe_tasks_archive.json
+    fn archive_path(&self) -> PathBuf {
+        let stem = self.file_path.file_stem().unwrap_or_default().to_string_lossy();
+        self.file_path.with_file_name(format!("{}_archive.json", stem))
+    }
+
+    // Backups live in a folder next to the tasks file, e.g. ~/.vibe_tasks.backups/
+    pub fn backups_dir(&self) -> PathBuf {
+        let stem = self.file_path.file_stem().unwrap_or_default().to_string_lossy();
+        self.file_path.with_file_name(format!("{}.backups", stem))
+    }
+
+    // Writes a full copy of the tasks to the given file, or to a timestamped file in the
+    // backups folder, returning where it went
+    pub fn backup(&self, path: Option<PathBuf>) -> Result<PathBuf, TaskError> {
+        let path = match path {
+            Some(path) => path,
+            None => self.backups_dir().join(format!("{}.json", Local::now().format("%Y%m%d-%H%M%S"))),
+        };
+        create_parent_dir(&path)?;
+        fs::write(&path, self.to_json()?)
+            .map_err(|e| TaskError::Io(format!("Could not write {}: {}", path.display(), e)))?;
+        Ok(path)
+    }
+
+    // Deletes all but the newest `keep` backups in the backups folder, returning how many went
+    pub fn prune_backups(&self, keep: usize) -> Result<usize, TaskError> {
+        let dir = self.backups_dir();
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| TaskError::Io(format!("Could not read {}: {}", dir.display(), e)))?;
+        // Timestamped names sort oldest first
+        let mut backups: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        backups.sort();
+        let excess = backups.len().saturating_sub(keep);
+        for path in &backups[..excess] {
+            fs::remove_file(path).map_err(|e| TaskError::Io(format!("Could not remove {}: {}", path.display(), e)))?;
+        }
+        Ok(excess)
+    }
+
+    // Replaces the tasks and categories with those in a backup file, returning how many tasks
+    // it has. Reminder times are kept for tasks that still exist, and ids handed out since the
+    // backup aren't reused. Call save() to write them out.
+    pub fn restore(&mut self, path: &Path) -> Result<usize, TaskError> {
+        if !path.exists() {
+            return Err(TaskError::Io(format!("Backup {} not found", path.display())));
+        }
+        let backup = TaskManager::new(path.to_path_buf())?;
+        let mut tasks = backup.tasks;
+        for task in &mut tasks {
+            task.last_notification = self.find(task.id).and_then(|current| current.last_notification);
+        }
+        self.tasks = tasks;
+        self.categories = backup.categories;
+        self.next_id = self.next_id.max(backup.next_id);
+        Ok(self.tasks.len())
+    }
+
+    // Loads archived tasks, or an empty list if nothing has been archived yet
+    pub fn load_archive(&self) -> Result<Vec<Task>, TaskError> {
+        let path = self.archive_path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = fs::read_to_string(&path)
+            .map_err(|e| TaskError::Io(format!("Could not read {}: {}", path.display(), e)))?;
+        serde_json::from_str(&data).map_err(|e| TaskError::Io(format!("Could not read {}: {}", path.display(), e)))
+    }
+
+    // Writes the archived tasks to the archive file
+    fn save_archive(&self, archived: &[Task]) -> Result<(), TaskError> {
+        let path = self.archive_path();
+        let data = serde_json::to_string_pretty(archived)
+            .map_err(|e| TaskError::Io(format!("Could not serialize archive: {}", e)))?;
+        fs::write(&path, data).map_err(|e| TaskError::Io(format!("Could not write {}: {}", path.display(), e)))
+    }
+
+    // Moves every Done task into the archive and saves, returning how many were moved. Like
+    // purge and unarchive, it refuses with ChangedOnDisk before writing either file.
+    pub fn archive_done(&mut self) -> Result<usize, TaskError> {
+        self.check_unchanged()?;
+        let (done, active): (Vec<Task>, Vec<Task>) =
+            self.tasks.drain(..).partition(|t| t.status == Status::Done);
+        self.tasks = active;
+        if done.is_empty() {
+            return Ok(0);
+        }
+
+        // Write the archive first so a failure can't lose tasks
+        let mut archived = self.load_archive()?;
+        self.archive_snapshot.get_or_insert_with(|| archived.clone());
+        let count = done.len();
+        archived.extend(done);
+        self.save_archive(&archived)?;
+        self.save()?;
+        Ok(count)
+    }
+
+    // Counts the Done tasks purge would delete, as (active, archived)
+    pub fn purgeable(&self, before: Option<NaiveDate>) -> Result<(usize, usize), TaskError> {
+        let cutoff = before.map(start_of_day);
+        let active = self.tasks.iter().filter(|t| is_purgeable(t, cutoff)).count();
+        let archived = self.load_archive()?.iter().filter(|t| is_purgeable(t, cutoff)).count();
+        Ok((active, archived))
+    }
+
+    // Deletes Done tasks, active and archived, finished before the given date (tasks without
+    // a completion time go by their creation time), saving both files; undo brings both back.
+    // Returns how many were deleted.
+    pub fn purge(&mut self, before: Option<NaiveDate>) -> Result<usize, TaskError> {
+        self.check_unchanged()?;
+        let cutoff = before.map(start_of_day);
+        let mut archived = self.load_archive()?;
+        let (active_before, archived_before) = (self.tasks.len(), archived.len());
+
+        let before_purge = archived.clone();
+        archived.retain(|t| !is_purgeable(t, cutoff));
+        if archived.len() < archived_before {
+            self.archive_snapshot.get_or_insert(before_purge);
+            self.save_archive(&archived)?;
+        }
+        self.tasks.retain(|t| !is_purgeable(t, cutoff));
+        // Saving the tasks file also records the archive for undo, so save when either changed
+        if self.tasks.len() < active_before || archived.len() < archived_before {
+            self.save()?;
+        }
+        Ok(active_before - self.tasks.len() + archived_before - archived.len())
+    }
+
+    // Moves an archived task back into the active list, saving both files. Returns the task's
+    // id, which is a new one if an active task has taken its old id in the meantime.
+    pub fn unarchive(&mut self, id: usize) -> Result<usize, TaskError> {
+        self.check_unchanged()?;
+        let mut archived = self.load_archive()?;
+        let Some(pos) = archived.iter().position(|t| t.id == id) else {
+            return Err(TaskError::NotArchived(id));
+        };
+        self.archive_snapshot.get_or_insert_with(|| archived.clone());
+        let mut task = archived.remove(pos);
+        if self.find(id).is_some() {
+            task.id = self.next_id;
+            self.next_id += 1;
+        }
+        let new_id = task.id;
+        task.rank = self.tasks.len() + 1;
+        self.tasks.push(task);
+        self.save()?;
+        self.save_archive(&archived)?;
+        Ok(new_id)
+    }
+
+    // Looks up a task by id
+    pub fn find(&self, id: usize) -> Option<&Task> {
+        self.tasks.iter().find(|t| t.id == id)
+    }
+
+    // Looks up a task by id when the selector is a number, otherwise by a unique title
+    // containing the text (ignoring case). When several titles contain it, one that
+    // matches in full wins.
+    pub fn resolve_task(&self, selector: &str) -> Result<&Task, TaskError> {
+        let selector = selector.trim();
+        if let Ok(id) = selector.parse::<usize>() {
+            return self.find(id).ok_or(TaskError::NotFound(id));
+        }
+
+        let text = selector.to_lowercase();
+        let matches: Vec<&Task> = self.tasks.iter().filter(|t| t.title.to_lowercase().contains(&text)).collect();
+        match matches.as_slice() {
+            [task] => Ok(task),
+            [] => Err(TaskError::NoTitleMatch(selector.to_string())),
+            _ => match matches.iter().find(|t| t.title.to_lowercase() == text) {
+                Some(task) => Ok(task),
+                None => Err(TaskError::AmbiguousTitle {
+                    text: selector.to_string(),
+                    candidates: matches.iter().map(|t| (t.id, t.title.clone())).collect(),
+                }),
+            },
+        }
+    }
+
+    // Looks up a task by id for changing it
+    pub fn find_mut(&mut self, id: usize) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|t| t.id == id)
+    }
+
+    // Looks up a task for changing it, or reports that it doesn't exist
+    fn task_mut(&mut self, id: usize) -> Result<&mut Task, TaskError> {
+        self.find_mut(id).ok_or(TaskError::NotFound(id))
+    }
+
+    // Finds a defined category by name, ignoring case and an optional emoji prefix ("work", "💼 Work")
+    pub fn resolve_category(&self, name: &str) -> Option<Category> {
+        let wanted = name.trim().to_lowercase();
+        self.categories
+            .iter()
+            .find(|c| {
+                let bare = wanted.strip_prefix(c.emoji.as_str()).unwrap_or(&wanted).trim();
+                bare == c.name.to_lowercase()
+            })
+            .cloned()
+    }
+
+    // Error for a category name that doesn't resolve, listing the defined ones
+    pub fn unknown_category(&self, name: &str) -> TaskError {
+        TaskError::UnknownCategory {
+            name: name.to_string(),
+            available: self.categories.iter().map(|c| c.name.clone()).collect(),
+        }
+    }
+
+    // Adds a category to a task, returning false if the task already had it. Fails with
+    // TooManyCategories if the task is already at the max_categories() limit.
+    pub fn add_category(&mut self, id: usize, category: Category) -> Result<bool, TaskError> {
+        let task = self.task_mut(id)?;
+        if task.categories.iter().any(|c| c.name == category.name) {
+            return Ok(false);
+        }
+        check_category_count(task.categories.len() + 1)?;
+        task.categories.push(category);
+        Ok(true)
+    }
+
+    // Replaces a task's defined categories with the selected ones; categories that are
+    // no longer defined can't be selected, so they are kept as they are. Leaves the task
+    // unchanged and fails with TooManyCategories if the result is over the limit.
+    pub fn set_categories(&mut self, id: usize, selected: Vec<Category>) -> Result<(), TaskError> {
+        let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) else {
+            return Err(TaskError::NotFound(id));
+        };
+        let mut categories = task.categories.clone();
+        categories.retain(|tc| !self.categories.iter().any(|c| c.name == tc.name));
+        categories.extend(selected);
+        categories.sort_by(|a, b| a.name.cmp(&b.name));
+        categories.dedup_by(|a, b| a.name == b.name);
+        check_category_count(categories.len())?;
+        task.categories = categories;
+        Ok(())
+    }
+
+    // Defines a new category, returning false if one with that name (ignoring case) exists
+    pub fn add_category_definition(&mut self, name: String, color: String, emoji: String) -> bool {
+        if self.categories.iter().any(|c| c.name.eq_ignore_ascii_case(&name)) {
+            return false;
+        }
+        self.categories.push(Category { name, color, emoji });
+        true
+    }
+
+    // Removes a category definition; tasks keep categories already assigned to them
+    pub fn remove_category_definition(&mut self, name: &str) -> Result<Category, TaskError> {
+        match self.categories.iter().position(|c| c.name.eq_ignore_ascii_case(name)) {
+            Some(pos) => Ok(self.categories.remove(pos)),
+            None => Err(TaskError::CategoryNotFound(name.to_string())),
+        }
+    }
+
+    // Starts time tracking for a task. Only one timer may run at a time: with `switch` the
+    // running one is stopped first, and its task's id returned.
+    pub fn start_timer(&mut self, id: usize, force: bool, switch: bool) -> Result<Option<usize>, TaskError> {
+        let Some(index) = self.tasks.iter().position(|t| t.id == id) else {
+            return Err(TaskError::NotFound(id));
+        };
+        if self.tasks[index].current_time_entry.is_some() {
+            return Err(TaskError::TimerAlreadyRunning(id));
+        }
+        if self.tasks[index].status == Status::Done && !force {
+            return Err(TaskError::AlreadyDone(id));
+        }
+
+        let mut stopped = None;
+        if let Some(busy) = self.tasks.iter_mut().find(|t| t.current_time_entry.is_some()) {
+            if !switch {
+                return Err(TaskError::TimerBusy { id: busy.id, title: busy.title.clone() });
+            }
+            busy.finish_current_entry();
+            stopped = Some(busy.id);
+        }
+
+        let task = &mut self.tasks[index];
+        task.current_time_entry = Some(TimeEntry {
+            start_time: Local::now(),
+            end_time: None,
+            duration: None,
+        });
+        task.paused = false;
+        Ok(stopped)
+    }
+
+    // Throws away a task's running session without recording it, returning how long it ran
+    pub fn clear_timer(&mut self, id: usize) -> Result<Duration, TaskError> {
+        let task = self.task_mut(id)?;
+        let current = task.current_time_entry.take().ok_or(TaskError::NoActiveTimer(id))?;
+        Ok(Local::now() - current.start_time)
+    }
+
+    // Stops time tracking for a task, whether it is running or paused
+    pub fn stop_timer(&mut self, id: usize) -> Result<(), TaskError> {
+        let task = self.task_mut(id)?;
+        if task.finish_current_entry().is_none() && !task.paused {
+            return Err(TaskError::NoActiveTimer(id));
+        }
+        task.paused = false;
+        Ok(())
+    }
+
+    // Pauses time tracking, recording the segment so far and remembering to resume
+    pub fn pause_timer(&mut self, id: usize) -> Result<(), TaskError> {
+        let task = self.task_mut(id)?;
+        if task.finish_current_entry().is_none() {
+            return Err(TaskError::NoActiveTimer(id));
+        }
+        task.paused = true;
+        Ok(())
+    }
+
+    // Resumes a paused task by starting a new time tracking segment
+    pub fn resume_timer(&mut self, id: usize) -> Result<(), TaskError> {
+        match self.find(id) {
+            Some(task) if !task.paused => Err(TaskError::TimerNotPaused(id)),
+            _ => self.start_timer(id, false, false).map(|_| ()),
+        }
+    }
+
+    // Sets the hourly billing rate for a task, or clears it when no rate is given
+    pub fn set_rate(&mut self, id: usize, rate: Option<f64>) -> Result<(), TaskError> {
+        self.task_mut(id)?.hourly_rate = rate;
+        Ok(())
+    }
+
+    // Sets or clears how many minutes a week should go into a task
+    pub fn set_goal(&mut self, id: usize, minutes: Option<u32>) -> Result<(), TaskError> {
+        self.task_mut(id)?.time_goal_minutes = minutes;
+        Ok(())
+    }
+
+    // Hours tracked per task in a category (any if None) for sessions started within the
+    // dates, inclusive; tasks without tracked time are left out
+    pub fn billable_hours(&self, category: Option<&str>, since: Option<NaiveDate>, until: Option<NaiveDate>) -> Vec<(&Task, f64)> {
+        let (since, until) = date_range(since, until);
+
+        self.tasks
+            .iter()
+            .filter(|t| {
+                category.is_none_or(|name| t.categories.iter().any(|c| c.name.eq_ignore_ascii_case(name)))
+            })
+            .map(|t| (t, t.tracked_between(since, until).num_seconds() as f64 / 3600.0))
+            .filter(|(_, hours)| *hours > 0.0)
+            .collect()
+    }
+
+    // Hours tracked since a date (all time if None) per category, most first. A task in
+    // several categories counts its full time toward each; tasks without one are "Uncategorized".
+    pub fn category_hours(&self, since: Option<NaiveDate>) -> Vec<(String, f64)> {
+        let since = since.map(start_of_day);
+        let mut totals: Vec<(String, f64)> = Vec::new();
+        for task in &self.tasks {
+            let hours = task.tracked_between(since, None).num_seconds() as f64 / 3600.0;
+            if hours <= 0.0 {
+                continue;
+            }
+            let mut names: Vec<&str> = task.categories.iter().map(|c| c.name.as_str()).collect();
+            if names.is_empty() {
+                names.push("Uncategorized");
+            }
+            for name in names {
+                match totals.iter_mut().find(|(total_name, _)| total_name == name) {
+                    Some((_, total)) => *total += hours,
+                    None => totals.push((name.to_string(), hours)),
+                }
+            }
+        }
+        totals.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        totals
+    }
+
+    // Sets or clears how many hours before its due date a task's priority is raised
+    pub fn set_escalation(&mut self, id: usize, hours: Option<i64>) -> Result<(), TaskError> {
+        self.task_mut(id)?.escalate_within = hours;
+        Ok(())
+    }
+
+    // Raises the priority of unfinished tasks nearing their due date one level above the
+    // original once within their escalation window (hours, `default_hours` unless the task
+    // sets its own; None leaves such tasks alone), overdue tasks included. Priorities are never
+    // lowered, so running this again changes nothing. Returns each change as (id, from, to).
+    pub fn escalate_priorities(&mut self, default_hours: Option<i64>, now: DateTime<Local>) -> Vec<(usize, Priority, Priority)> {
+        let mut changes = Vec::new();
+        for task in self.tasks.iter_mut().filter(|t| t.status != Status::Done) {
+            let (Some(hours), Some(time_until_due)) = (task.escalate_within.or(default_hours), task.time_until_due(now)) else {
+                continue;
+            };
+            if time_until_due > Duration::hours(hours) {
+                continue;
+            }
+
+            let original = task.escalated_from.clone().unwrap_or_else(|| task.priority.clone());
+            let target = original.raised();
+            if target > task.priority {
+                changes.push((task.id, task.priority.clone(), target.clone()));
+                task.escalated_from = Some(original);
+                task.priority = target;
+            }
+        }
+        changes
+    }
+
+    // Tasks due within their reminder lead time (hours) that haven't been reminded about
+    // in the last `throttle_hours`, each with the reminder text to show. Urgent tasks are
+    // also reminded about once overdue, and at most hourly rather than per the throttle.
+    pub fn due_notifications(&self, lead_hours: i64, throttle_hours: i64, now: DateTime<Local>) -> Vec<(usize, String)> {
+        self.tasks
+            .iter()
+            .filter(|task| task.status != Status::Done)
+            .filter_map(|task| {
+                let time_until_due = task.time_until_due(now)?;
+                let urgent = task.priority == Priority::Urgent;
+                let lead_hours = task.notify_before.unwrap_or(lead_hours);
+                if time_until_due.num_hours() > lead_hours || (time_until_due < Duration::zero() && !urgent) {
+                    return None;
+                }
+                let throttle_hours = if urgent { throttle_hours.min(1) } else { throttle_hours };
+                if task.last_notification.is_some_and(|last| (now - last).num_hours() < throttle_hours) {
+                    return None;
+                }
+
+                let text = if time_until_due < Duration::zero() {
+                    format!("Task '{}' is overdue by {}!", task.title, humanize(time_until_due))
+                } else if time_until_due.num_hours() == 0 {
+                    format!("Task '{}' is due now!", task.title)
+                } else {
+                    format!("Task '{}' is due in {} hours!", task.title, time_until_due.num_hours())
+                };
+                Some((task.id, text))
+            })
+            .collect()
+    }
+
+    // Assigns the next free id to a task and adds it, returning the id
+    pub fn add_task(&mut self, mut task: Task) -> usize {
+        let task_id = self.next_id;
+        self.next_id += 1;
+        task.id = task_id;
+        task.rank = self.tasks.len() + 1;
+        self.tasks.push(task);
+        task_id
+    }
+
+    // Whether a task with the same title (ignoring case) and due date already exists
+    pub fn is_duplicate(&self, task: &Task) -> bool {
+        self.tasks
+            .iter()
+            .any(|t| t.title.to_lowercase() == task.title.to_lowercase() && t.due_date == task.due_date)
+    }
+
+    // IDs of the task's dependencies that still exist and aren't done yet
+    pub fn open_dependencies(&self, task: &Task) -> Vec<usize> {
+        if task.status == Status::Done {
+            return Vec::new();
+        }
+        task.depends_on
+            .iter()
+            .copied()
+            .filter(|dep| self.find(*dep).is_some_and(|t| t.status != Status::Done))
+            .collect()
+    }
+
+    // Whether `from` already depends on `target`, directly or through other tasks
+    fn depends_transitively(&self, from: usize, target: usize) -> bool {
+        let mut stack = vec![from];
+        let mut seen = Vec::new();
+        while let Some(id) = stack.pop() {
+            if id == target {
+                return true;
+            }
+            if seen.contains(&id) {
+                continue;
+            }
+            seen.push(id);
+            if let Some(task) = self.find(id) {
+                stack.extend(&task.depends_on);
+            }
+        }
+        false
+    }
+
+    // Records that task `id` can't be finished before task `on`, or removes that link.
+    // Returns false if there was nothing to change.
+    pub fn set_dependency(&mut self, id: usize, on: usize, remove: bool) -> Result<bool, TaskError> {
+        if self.find(on).is_none() {
+            return Err(TaskError::NotFound(on));
+        }
+        if id == on {
+            return Err(TaskError::SelfDependency);
+        }
+        if !remove && self.depends_transitively(on, id) {
+            return Err(TaskError::DependencyCycle { id, on });
+        }
+
+        let task = self.task_mut(id)?;
+        if task.depends_on.contains(&on) != remove {
+            return Ok(false);
+        }
+        if remove {
+            task.depends_on.retain(|dep| *dep != on);
+        } else {
+            task.depends_on.push(on);
+        }
+        Ok(true)
+    }
+
+    // The most pressing open task that isn't blocked, if any
+    pub fn next_task(&self, now: DateTime<Local>) -> Option<&Task> {
+        self.tasks
+            .iter()
+            .filter(|t| t.status != Status::Done && self.open_dependencies(t).is_empty())
+            .max_by(|a, b| task_score(a, now).total_cmp(&task_score(b, now)))
+    }
+
+    // Tasks whose title, description or category names contain the query, ignoring case
+    pub fn search(&self, query: &str) -> Vec<&Task> {
+        let needle = query.to_lowercase();
+        self.tasks
+            .iter()
+            .filter(|t| {
+                t.title.to_lowercase().contains(&needle)
+                    || t.description.as_ref().is_some_and(|d| d.to_lowercase().contains(&needle))
+                    || t.categories.iter().any(|c| c.name.to_lowercase().contains(&needle))
+            })
+            .collect()
+    }
+
+    // Sets a task's percent complete, clamped to 0-100
+    pub fn set_progress(&mut self, id: usize, percent: i64) -> Result<&Task, TaskError> {
+        let task = self.task_mut(id)?;
+        task.progress = percent.clamp(0, 100) as u8;
+        Ok(task)
+    }
+
+    // Copies a task's title, description, priority, categories and due date into a fresh
+    // Todo task, returning the new task's id
+    pub fn duplicate_task(&mut self, id: usize) -> Result<usize, TaskError> {
+        let Some(original) = self.find(id) else {
+            return Err(TaskError::NotFound(id));
+        };
+        let mut copy = Task::new(original.title.clone(), original.priority.clone());
+        copy.description = original.description.clone();
+        copy.categories = original.categories.clone();
+        copy.due_date = original.due_date;
+        Ok(self.add_task(copy))
+    }
+
+    // Adds free-form tags to a task, or removes them; a leading # and case are ignored
+    pub fn tag_task(&mut self, id: usize, tags: &[String], remove: bool) -> Result<(), TaskError> {
+        let task = self.task_mut(id)?;
+        for tag in tags.iter().map(|tag| normalize_tag(tag)).filter(|tag| !tag.is_empty()) {
+            let existing = task.tags.iter().position(|t| t.eq_ignore_ascii_case(tag));
+            match (existing, remove) {
+                (None, false) => task.tags.push(tag.to_string()),
+                (Some(pos), true) => {
+                    task.tags.remove(pos);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Pushes a task's due date back (or sets it from now) and lets its reminder fire again,
+    // returning the new due date
+    pub fn snooze_task(&mut self, id: usize, duration: Duration) -> Result<DateTime<Local>, TaskError> {
+        let task = self.task_mut(id)?;
+        let due = task.due_date.unwrap_or_else(Local::now) + duration;
+        task.due_date = Some(due);
+        task.last_notification = None;
+        Ok(due)
+    }
+
+    // Appends a timestamped note to a task's progress log
+    pub fn add_note(&mut self, id: usize, text: String) -> Result<(), TaskError> {
+        self.task_mut(id)?.notes.push(Note { text, created_at: Local::now() });
+        Ok(())
+    }
+
+    // Appends a checklist item to a task, returning its 1-based index
+    pub fn add_subtask(&mut self, id: usize, title: String) -> Result<usize, TaskError> {
+        let task = self.task_mut(id)?;
+        task.subtasks.push(Subtask { title, done: false });
+        Ok(task.subtasks.len())
+    }
+
+    // Flips a checklist item between done and not done (index is 1-based)
+    pub fn toggle_subtask(&mut self, id: usize, index: usize) -> Result<&Task, TaskError> {
+        let task = self.task_mut(id)?;
+        let Some(subtask) = index.checked_sub(1).and_then(|i| task.subtasks.get_mut(i)) else {
+            return Err(TaskError::SubtaskNotFound { id, index });
+        };
+        subtask.done = !subtask.done;
+        Ok(task)
+    }
+
+    // Marks a task as complete, stopping its timer. Blocked tasks are refused unless forced.
+    // Recurring tasks spawn their next occurrence the first time they are completed.
+    pub fn complete_task(&mut self, id: usize, force: bool) -> Result<Completion, TaskError> {
+        let Some(index) = self.tasks.iter().position(|t| t.id == id) else {
+            return Err(TaskError::NotFound(id));
+        };
+        let blockers = self.open_dependencies(&self.tasks[index]);
+        if !blockers.is_empty() && !force {
+            return Err(TaskError::Blocked { id, blockers });
+        }
+
+        // A reopened recurring task may already have spawned its next occurrence
+        let already_spawned = self.tasks[index]
+            .next_occurrence
+            .is_some_and(|next_id| self.find(next_id).is_some());
+
+        let task = &mut self.tasks[index];
+        let was_done = task.status == Status::Done;
+        task.status = Status::Done;
+        if !was_done {
+            task.completed_at = Some(Local::now());
+        }
+
+        // A finished task shouldn't keep a timer running
+        let stopped_timer = task.finish_current_entry().is_some();
+        task.paused = false;
+
+        let next = match &task.recurrence {
+            Some(recurrence) if !was_done && !already_spawned => {
+                // Escalation applied to this occurrence doesn't carry over
+                let priority = task.escalated_from.clone().unwrap_or_else(|| task.priority.clone());
+                let mut next = Task::new(task.title.clone(), priority);
+                next.description = task.description.clone();
+                next.categories = task.categories.clone();
+                next.recurrence = task.recurrence.clone();
+                next.due_date = Some(task.due_date.unwrap_or_else(Local::now) + recurrence.interval());
+                Some(next)
+            }
+            _ => None,
+        };
+        let next_occurrence = next.map(|next| self.add_task(next));
+        if next_occurrence.is_some() {
+            self.tasks[index].next_occurrence = next_occurrence;
+        }
+        Ok(Completion { stopped_timer, next_occurrence })
+    }
+
+    // Moves a Done task back to In Progress (or Todo), returning false if it wasn't done
+    pub fn reopen_task(&mut self, id: usize, todo: bool) -> Result<bool, TaskError> {
+        let task = self.task_mut(id)?;
+        if task.status != Status::Done {
+            return Ok(false);
+        }
+        task.status = if todo { Status::Todo } else { Status::InProgress };
+        task.completed_at = None;
+        Ok(true)
+    }
+
+    // Sets the status of the given tasks that exist, returning a NotFound error for each
+    // id that doesn't; a missing id doesn't stop the others from changing
+    pub fn set_status(&mut self, ids: &[usize], status: &Status) -> Vec<TaskError> {
+        for task in self.tasks.iter_mut().filter(|t| ids.contains(&t.id)) {
+            task.set_status(status);
+        }
+        ids.iter().filter(|id| self.find(**id).is_none()).map(|id| TaskError::NotFound(*id)).collect()
+    }
+
+    // IDs of the tasks whose title contains the text, ignoring case
+    pub fn ids_with_title(&self, text: &str) -> Vec<usize> {
+        let text = text.to_lowercase();
+        self.tasks.iter().filter(|t| t.title.to_lowercase().contains(&text)).map(|t| t.id).collect()
+    }
+
+    // IDs of the tasks in a category and/or with a priority. Category names resolve like
+    // everywhere else, but categories that were since removed still match by name.
+    pub fn ids_matching(&self, category: Option<&str>, priority: Option<&Priority>) -> Vec<usize> {
+        let category = category.map(|name| {
+            self.resolve_category(name).map_or_else(|| name.trim().to_string(), |c| c.name)
+        });
+        self.tasks
+            .iter()
+            .filter(|t| {
+                category.as_ref().is_none_or(|name| t.categories.iter().any(|c| c.name.eq_ignore_ascii_case(name)))
+                    && priority.is_none_or(|p| &t.priority == p)
+            })
+            .map(|t| t.id)
+            .collect()
+    }
+
+    // Removes a task from the list, returning it
+    pub fn delete_task(&mut self, id: usize) -> Result<Task, TaskError> {
+        match self.tasks.iter().position(|t| t.id == id) {
+            Some(pos) => Ok(self.tasks.remove(pos)),
+            None => Err(TaskError::NotFound(id)),
+        }
+    }
+
+    // Numbers the ranks 1, 2, 3… in their current order, with ties kept in file order, so
+    // deleting, archiving or purging tasks leaves no gaps
+    fn renumber_ranks(&mut self) {
+        let mut order: Vec<usize> = (0..self.tasks.len()).collect();
+        order.sort_by_key(|&i| self.tasks[i].rank);
+        for (rank, i) in order.into_iter().enumerate() {
+            self.tasks[i].rank = rank + 1;
+        }
+    }
+
+    // Moves a task to a rank (clamped to the list), shifting the tasks in between, and
+    // returns the rank it ended up at
+    pub fn move_task(&mut self, id: usize, to: usize) -> Result<usize, TaskError> {
+        self.renumber_ranks();
+        let from = self.find(id).ok_or(TaskError::NotFound(id))?.rank;
+        let to = to.clamp(1, self.tasks.len());
+        for task in &mut self.tasks {
+            if task.id == id {
+                task.rank = to;
+            } else if from < to && (from + 1..=to).contains(&task.rank) {
+                task.rank -= 1;
+            } else if to < from && (to..from).contains(&task.rank) {
+                task.rank += 1;
+            }
+        }
+        Ok(to)
+    }
+}
+
+// Whether purge should delete a task: Done, and finished (or created) before the cutoff if one is given
+fn is_purgeable(task: &Task, cutoff: Option<DateTime<Local>>) -> bool {
+    task.status == Status::Done
+        && cutoff.is_none_or(|cutoff| task.completed_at.unwrap_or(task.created_at) < cutoff)
+}
+
+// Smell is a planning problem found by lint, with the tasks that have it
+#[derive(Debug, Serialize)]
+pub struct Smell {
+    pub problem: &'static str,                // What's wrong, e.g. "High or Urgent with no due date"
+    pub suggestion: &'static str,             // How to fix it
+    pub ids: Vec<usize>,                      // Tasks with the problem
+}
+
+// A lint check: the problem, how to fix it, and which tasks have it
+type LintCheck = (&'static str, &'static str, fn(&Task, DateTime<Local>) -> bool);
+
+// Every check lint runs, in the order they're reported; add new ones here
+const LINT_CHECKS: [LintCheck; 4] = [
+    ("High or Urgent with no due date", "give them one with edit, or lower the priority", important_without_due),
+    ("In progress with no time tracked", "track work with start-time, or set them back to Todo", in_progress_untracked),
+    ("Overdue by more than a week", "reschedule with snooze or edit, or delete them if they no longer matter", long_overdue),
+    ("Every subtask done but still open", "mark them complete", checklist_done),
+];
+
+fn important_without_due(task: &Task, _now: DateTime<Local>) -> bool {
+    task.status != Status::Done && task.priority >= Priority::High && task.due_date.is_none()
+}
+
+fn in_progress_untracked(task: &Task, _now: DateTime<Local>) -> bool {
+    task.status == Status::InProgress && task.time_entries.is_empty() && task.current_time_entry.is_none()
+}
+
+fn long_overdue(task: &Task, now: DateTime<Local>) -> bool {
+    task.is_overdue(now) && task.time_until_due(now).is_some_and(|left| left < -Duration::weeks(1))
+}
+
+fn checklist_done(task: &Task, _now: DateTime<Local>) -> bool {
+    task.status != Status::Done && !task.subtasks.is_empty() && task.subtasks.iter().all(|s| s.done)
+}
+
+// Runs every lint check, returning the smells that at least one task has
+pub fn lint(tasks: &[Task], now: DateTime<Local>) -> Vec<Smell> {
+    LINT_CHECKS
+        .iter()
+        .map(|&(problem, suggestion, check)| Smell {
+            problem,
+            suggestion,
+            ids: tasks.iter().filter(|task| check(task, now)).map(|task| task.id).collect(),
+        })
+        .filter(|smell| !smell.ids.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    // A closed time tracking session of the given length, starting at `start`
+    fn entry(start: DateTime<Local>, seconds: i64) -> TimeEntry {
+        let end = start + Duration::seconds(seconds);
+        TimeEntry { start_time: start, end_time: Some(end), duration: Some(end - start) }
+    }
+
+    #[test]
+    fn total_tracked_sums_closed_entries() {
+        let start = Local::now() - Duration::days(1);
+        let mut task = Task::new("Write report".to_string(), Priority::Medium);
+        task.time_entries = vec![entry(start, 3600), entry(start + Duration::hours(2), 1800), entry(start + Duration::hours(4), 5)];
+        assert_eq!(task.total_tracked(), Duration::seconds(5405));
+    }
+
+    #[test]
+    fn total_tracked_includes_running_entry() {
+        let now = Local::now();
+        let mut task = Task::new("Write report".to_string(), Priority::Medium);
+        task.time_entries = vec![entry(now - Duration::hours(3), 600)];
+        task.current_time_entry = Some(TimeEntry { start_time: now - Duration::seconds(90), end_time: None, duration: None });
+        let total = task.total_tracked();
+        assert!(total >= Duration::seconds(690) && total < Duration::seconds(700), "{}", total);
+    }
+
+    #[test]
+    fn total_tracked_is_zero_without_entries() {
+        let task = Task::new("Write report".to_string(), Priority::Medium);
+        assert_eq!(task.total_tracked(), Duration::zero());
+    }
+
+    #[test]
+    fn total_tracked_keeps_sub_minute_entries() {
+        let start = Local::now() - Duration::hours(1);
+        let mut task = Task::new("Write report".to_string(), Priority::Medium);
+        task.time_entries = vec![entry(start, 30), entry(start + Duration::minutes(5), 45)];
+        assert_eq!(task.total_tracked(), Duration::seconds(75));
+    }
+
+    #[test]
+    fn tracked_between_counts_sessions_started_in_range() {
+        let now = Local::now();
+        let mut task = Task::new("Write report".to_string(), Priority::Medium);
+        task.time_entries = vec![entry(now - Duration::days(3), 100), entry(now - Duration::hours(2), 200)];
+        assert_eq!(task.tracked_between(Some(now - Duration::days(1)), None), Duration::seconds(200));
+        assert_eq!(task.tracked_between(None, Some(now - Duration::days(1))), Duration::seconds(100));
+    }
+
+    // An empty manager whose file is never written by these tests
+    fn manager() -> TaskManager {
+        TaskManager::new(std::env::temp_dir().join("vibe_tasks_test_missing.json")).unwrap()
+    }
+
+    // Adds a task with the given title, returning its id
+    fn add(manager: &mut TaskManager, title: &str) -> usize {
+        manager.add_task(Task::new(title.to_string(), Priority::Medium))
+    }
+
+    #[test]
+    fn ids_are_not_reused_after_delete() {
+        let mut manager = manager();
+        let ids: Vec<usize> = ["a", "b", "c"].iter().map(|title| add(&mut manager, title)).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+
+        manager.delete_task(2).unwrap();
+        assert_eq!(add(&mut manager, "d"), 4);
+        manager.delete_task(4).unwrap();
+        assert_eq!(add(&mut manager, "e"), 5);
+        assert_eq!(manager.next_id, 6);
+
+        let ids: Vec<usize> = manager.tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn next_id_is_recovered_from_old_files() {
+        let path = std::env::temp_dir().join(format!("vibe_tasks_test_legacy_{}.json", std::process::id()));
+        let tasks: Vec<Task> = [3, 7].iter().map(|&id| Task { id, ..Task::new(format!("Task {}", id), Priority::Low) }).collect();
+        fs::write(&path, serde_json::to_string(&tasks).unwrap()).unwrap();
+        let mut manager = TaskManager::new(path.clone()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(manager.tasks.len(), 2);
+        assert_eq!(add(&mut manager, "new"), 8);
+    }
+
+
+    #[test]
+    fn starting_a_running_timer_again_is_refused() {
+        let mut manager = manager();
+        let id = add(&mut manager, "a");
+        manager.start_timer(id, false, false).unwrap();
+        assert!(matches!(manager.start_timer(id, false, false), Err(TaskError::TimerAlreadyRunning(i)) if i == id));
+        // Even --switch doesn't restart the task's own timer
+        assert!(matches!(manager.start_timer(id, false, true), Err(TaskError::TimerAlreadyRunning(i)) if i == id));
+        assert!(manager.find(id).unwrap().time_entries.is_empty());
+    }
+
+    #[test]
+    fn starting_a_done_task_needs_force() {
+        let mut manager = manager();
+        let id = add(&mut manager, "a");
+        manager.complete_task(id, false).unwrap();
+        assert!(matches!(manager.start_timer(id, false, false), Err(TaskError::AlreadyDone(i)) if i == id));
+        assert!(manager.find(id).unwrap().current_time_entry.is_none());
+        manager.start_timer(id, true, false).unwrap();
+        assert!(manager.find(id).unwrap().current_time_entry.is_some());
+    }
+
+    #[test]
+    fn completing_stops_the_running_timer() {
+        let mut manager = manager();
+        let id = add(&mut manager, "a");
+        manager.start_timer(id, false, false).unwrap();
+        let completion = manager.complete_task(id, false).unwrap();
+
+        assert!(completion.stopped_timer);
+        let task = manager.find(id).unwrap();
+        assert!(task.current_time_entry.is_none());
+        assert_eq!(task.time_entries.len(), 1);
+        assert!(task.time_entries[0].end_time.is_some() && task.time_entries[0].duration.is_some());
+    }
+
+    #[test]
+    fn completing_without_a_timer_records_nothing() {
+        let mut manager = manager();
+        let id = add(&mut manager, "a");
+        assert!(!manager.complete_task(id, false).unwrap().stopped_timer);
+        assert!(manager.find(id).unwrap().time_entries.is_empty());
+    }
+
+
+    // A local wall-clock time, for building expected dates
+    fn local(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Local> {
+        local_from_naive(NaiveDate::from_ymd_opt(y, mo, d).unwrap().and_hms_opt(h, mi, 0).unwrap())
+    }
+
+    #[test]
+    fn parses_due_date_forms() {
+        // Wednesday 2024-06-05 10:00
+        let now = local(2024, 6, 5, 10, 0);
+        let cases = [
+            ("2024-06-01 14:00", local(2024, 6, 1, 14, 0)),
+            ("2024-06-20", local(2024, 6, 20, 23, 59)),
+            ("+3d", now + Duration::days(3)),
+            ("+90m", now + Duration::minutes(90)),
+            ("+1w", now + Duration::weeks(1)),
+            ("in 2 hours", now + Duration::hours(2)),
+            ("in 1 day", now + Duration::days(1)),
+            ("in 30 minutes", now + Duration::minutes(30)),
+            ("in 2 weeks", now + Duration::weeks(2)),
+            ("today", local(2024, 6, 5, 23, 59)),
+            ("today 17:00", local(2024, 6, 5, 17, 0)),
+            ("Tomorrow", local(2024, 6, 6, 23, 59)),
+            ("tomorrow 9:30", local(2024, 6, 6, 9, 30)),
+            ("friday", local(2024, 6, 7, 23, 59)),
+            ("next monday 09:00", local(2024, 6, 10, 9, 0)),
+            // The same weekday means next week's
+            ("wednesday", local(2024, 6, 12, 23, 59)),
+            ("  2024-06-01 14:00  ", local(2024, 6, 1, 14, 0)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_due_date_from(input, now, END_OF_DAY), Ok(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn date_only_due_dates_use_the_given_time() {
+        let now = local(2024, 6, 5, 10, 0);
+        let five_pm = NaiveTime::from_hms_opt(17, 0, 0).unwrap();
+        assert_eq!(parse_due_date_from("2024-06-20", now, five_pm), Ok(local(2024, 6, 20, 17, 0)));
+        assert_eq!(parse_due_date_from("friday", now, five_pm), Ok(local(2024, 6, 7, 17, 0)));
+        // An explicit time still wins
+        assert_eq!(parse_due_date_from("friday 08:00", now, five_pm), Ok(local(2024, 6, 7, 8, 0)));
+    }
+
+    #[test]
+    fn rejects_invalid_due_dates() {
+        let now = local(2024, 6, 5, 10, 0);
+        for input in ["", "soon", "2024-13-01 10:00", "2024-02-30", "+3x", "+d", "in two hours", "in 2 fortnights", "today 25:00", "next moonday"] {
+            assert!(parse_due_date_from(input, now, END_OF_DAY).is_err(), "{}", input);
+        }
+    }
+
+
+    // An open task with the given priority, due this far from `now` if given
+    fn scored(priority: Priority, due_in: Option<Duration>, now: DateTime<Local>) -> f64 {
+        let mut task = Task::new("a".to_string(), priority);
+        task.due_date = due_in.map(|left| now + left);
+        task_score(&task, now)
+    }
+
+    #[test]
+    fn higher_priority_scores_higher() {
+        let now = Local::now();
+        let scores: Vec<f64> = Priority::ALL.iter().map(|p| scored(p.clone(), None, now)).collect();
+        assert!(scores.windows(2).all(|pair| pair[0] < pair[1]), "{:?}", scores);
+        let due = Some(Duration::days(3));
+        assert!(scored(Priority::High, due, now) > scored(Priority::Low, due, now));
+    }
+
+    #[test]
+    fn overdue_outranks_any_priority() {
+        let now = Local::now();
+        let overdue_low = scored(Priority::Low, Some(-Duration::hours(1)), now);
+        assert!(overdue_low > scored(Priority::Urgent, None, now));
+        assert!(overdue_low > scored(Priority::Urgent, Some(Duration::minutes(30)), now));
+        // Longer overdue ranks higher still
+        assert!(scored(Priority::Low, Some(-Duration::days(3)), now) > overdue_low);
+    }
+
+    #[test]
+    fn sooner_due_scores_higher() {
+        let now = Local::now();
+        let soon = scored(Priority::Medium, Some(Duration::hours(2)), now);
+        let later = scored(Priority::Medium, Some(Duration::days(5)), now);
+        assert!(soon > later);
+        assert!(later > scored(Priority::Medium, None, now));
+    }
+
+    #[test]
+    fn done_tasks_score_zero() {
+        let now = Local::now();
+        let mut task = Task::new("a".to_string(), Priority::Urgent);
+        task.due_date = Some(now - Duration::days(2));
+        task.status = Status::Done;
+        assert_eq!(task_score(&task, now), 0.0);
+    }
+
+
+    #[test]
+    fn switching_timers_closes_the_running_entry() {
+        let mut manager = manager();
+        let (a, b) = (add(&mut manager, "a"), add(&mut manager, "b"));
+        manager.start_timer(a, false, false).unwrap();
+
+        assert!(matches!(manager.start_timer(b, false, false), Err(TaskError::TimerBusy { id, .. }) if id == a));
+        assert!(manager.find(b).unwrap().current_time_entry.is_none());
+
+        assert_eq!(manager.start_timer(b, false, true).unwrap(), Some(a));
+        let task_a = manager.find(a).unwrap();
+        assert!(task_a.current_time_entry.is_none());
+        assert_eq!(task_a.time_entries.len(), 1);
+        assert!(task_a.time_entries[0].end_time.is_some());
+        let task_b = manager.find(b).unwrap();
+        assert!(task_b.current_time_entry.is_some());
+        assert!(task_b.time_entries.is_empty());
+    }
+
+
+    #[test]
+    fn parses_every_priority_and_status() {
+        let priorities = [("low", Priority::Low), ("Medium", Priority::Medium), ("HIGH", Priority::High), (" urgent ", Priority::Urgent)];
+        for (input, expected) in priorities {
+            assert_eq!(input.parse::<Priority>(), Ok(expected), "{}", input);
+        }
+        let statuses = [("todo", Status::Todo), ("in-progress", Status::InProgress), ("In Progress", Status::InProgress), ("DONE", Status::Done)];
+        for (input, expected) in statuses {
+            assert_eq!(input.parse::<Status>(), Ok(expected), "{}", input);
+        }
+    }
+
+    #[test]
+    fn displayed_names_parse_back() {
+        for priority in Priority::ALL {
+            assert_eq!(priority.to_string().parse::<Priority>(), Ok(priority));
+        }
+        for status in Status::ALL {
+            assert_eq!(status.to_string().parse::<Status>(), Ok(status));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_priorities_and_statuses() {
+        for input in ["", "critical", "hi", "1"] {
+            assert!(input.parse::<Priority>().is_err(), "{}", input);
+        }
+        for input in ["", "doing", "in_progress", "finished"] {
+            assert!(input.parse::<Status>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn parses_recurrences() {
+        let cases = [
+            ("daily", Recurrence::Daily),
+            ("Weekly", Recurrence::Weekly),
+            ("3", Recurrence::EveryDays(3)),
+            ("3d", Recurrence::EveryDays(3)),
+            ("2w", Recurrence::EveryDays(14)),
+            ("48h", Recurrence::EveryDays(2)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_recurrence(input), Ok(expected), "{}", input);
+        }
+        for input in ["", "0", "-2", "0d", "36h", "30m", "monthly", "3x"] {
+            assert!(parse_recurrence(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn parses_durations() {
+        let cases = [
+            ("30m", Duration::minutes(30)),
+            ("3h", Duration::hours(3)),
+            ("2d", Duration::days(2)),
+            ("1W", Duration::weeks(1)),
+            (" 15m ", Duration::minutes(15)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_duration(input), Ok(expected), "{}", input);
+        }
+        for input in ["", "h", "3", "3y", "three days", "1.5h"] {
+            assert!(parse_duration(input).is_err(), "{}", input);
+        }
+    }
+
+
+    // Central European time for 2024: UTC+1, and UTC+2 from 31 March 01:00 UTC until
+    // 27 October 01:00 UTC, so the DST tests don't depend on the machine's time zone
+    #[derive(Clone, Copy, Debug)]
+    struct Berlin2024;
+
+    impl Berlin2024 {
+        fn winter() -> FixedOffset {
+            FixedOffset::east_opt(3600).unwrap()
+        }
+
+        fn summer() -> FixedOffset {
+            FixedOffset::east_opt(7200).unwrap()
+        }
+    }
+
+    impl TimeZone for Berlin2024 {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Berlin2024
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<FixedOffset> {
+            // Each offset that maps back to this wall-clock time
+            let offsets: Vec<FixedOffset> = [Self::winter(), Self::summer()]
+                .into_iter()
+                .filter(|offset| self.offset_from_utc_datetime(&(*local - *offset)) == *offset)
+                .collect();
+            match offsets.as_slice() {
+                [offset] => MappedLocalTime::Single(*offset),
+                [a, b] => MappedLocalTime::Ambiguous(*a, *b),
+                _ => MappedLocalTime::None,
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let summer_start = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap().and_hms_opt(1, 0, 0).unwrap();
+            let summer_end = NaiveDate::from_ymd_opt(2024, 10, 27).unwrap().and_hms_opt(1, 0, 0).unwrap();
+            if (summer_start..summer_end).contains(utc) { Self::summer() } else { Self::winter() }
+        }
+    }
+
+    fn naive(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d).unwrap().and_hms_opt(h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn wall_clock_times_keep_their_offset_across_dst() {
+        // Set in winter, due in summer: still noon on the wall clock
+        let winter = wall_clock_time(&Berlin2024, naive(2024, 1, 10, 12, 0));
+        let summer = wall_clock_time(&Berlin2024, naive(2024, 7, 1, 12, 0));
+        assert_eq!(winter.naive_utc(), naive(2024, 1, 10, 11, 0));
+        assert_eq!(summer.naive_utc(), naive(2024, 7, 1, 10, 0));
+        assert_eq!(summer.naive_local(), naive(2024, 7, 1, 12, 0));
+    }
+
+    #[test]
+    fn skipped_spring_forward_times_move_past_the_gap() {
+        // 02:00-03:00 doesn't exist on 31 March; 02:30 becomes 03:30 summer time
+        let due = wall_clock_time(&Berlin2024, naive(2024, 3, 31, 2, 30));
+        assert_eq!(due.naive_local(), naive(2024, 3, 31, 3, 30));
+        assert_eq!(due.naive_utc(), naive(2024, 3, 31, 1, 30));
+        // Just either side of the gap is unaffected
+        assert_eq!(wall_clock_time(&Berlin2024, naive(2024, 3, 31, 1, 59)).naive_utc(), naive(2024, 3, 31, 0, 59));
+        assert_eq!(wall_clock_time(&Berlin2024, naive(2024, 3, 31, 3, 0)).naive_utc(), naive(2024, 3, 31, 1, 0));
+    }
+
+    #[test]
+    fn repeated_fall_back_times_take_the_first_occurrence() {
+        // 02:00-03:00 happens twice on 27 October; 02:30 means the earlier, summer time one
+        let due = wall_clock_time(&Berlin2024, naive(2024, 10, 27, 2, 30));
+        assert_eq!(due.naive_utc(), naive(2024, 10, 27, 0, 30));
+        assert_eq!(due.naive_local(), naive(2024, 10, 27, 2, 30));
+        assert_eq!(wall_clock_time(&Berlin2024, naive(2024, 10, 27, 3, 0)).naive_utc(), naive(2024, 10, 27, 2, 0));
+    }
+
+
+    #[test]
+    fn reserved_file_names_are_not_lists() {
+        for name in ["config", "templates", "work_archive", "", "a.b", "../x"] {
+            assert!(matches!(TaskManager::list_path(name), Err(TaskError::InvalidListName(_))), "{}", name);
+        }
+        assert!(TaskManager::list_path("templates-2").is_ok());
+        assert!(TaskManager::list_path("work").is_ok());
+    }
+
+    // A manager over a fresh file in its own temp folder, for tests that save
+    fn saved_manager(name: &str) -> (TaskManager, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("vibe_tasks_test_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        (manager, dir)
+    }
+
+    #[test]
+    fn undo_after_archive_does_not_duplicate_tasks() {
+        let (mut manager, dir) = saved_manager("undo_archive");
+        let id = add(&mut manager, "done");
+        manager.find_mut(id).unwrap().status = Status::Done;
+        manager.save().unwrap();
+
+        let mut manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        assert_eq!(manager.archive_done().unwrap(), 1);
+        let mut manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        manager.undo().unwrap().unwrap();
+
+        let manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        assert!(manager.find(id).is_some());
+        assert!(manager.load_archive().unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn undo_after_unarchive_puts_the_task_back_in_the_archive() {
+        let (mut manager, dir) = saved_manager("undo_unarchive");
+        let id = add(&mut manager, "done");
+        manager.find_mut(id).unwrap().status = Status::Done;
+        manager.save().unwrap();
+        let mut manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        manager.archive_done().unwrap();
+
+        let mut manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        manager.unarchive(id).unwrap();
+        let mut manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        manager.undo().unwrap().unwrap();
+
+        let manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        assert!(manager.find(id).is_none());
+        assert_eq!(manager.load_archive().unwrap().iter().map(|t| t.id).collect::<Vec<_>>(), vec![id]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+
+    #[test]
+    fn numbers_resolve_only_by_id() {
+        let mut manager = manager();
+        let id = add(&mut manager, "Pay invoice 7");
+        assert_eq!(manager.resolve_task(&id.to_string()).unwrap().id, id);
+        assert!(matches!(manager.resolve_task("7"), Err(TaskError::NotFound(7))));
+        assert_eq!(manager.resolve_task("invoice 7").unwrap().id, id);
+    }
+
+
+    #[test]
+    fn category_limit_applies_to_every_way_of_assigning() {
+        // The only test that reads the limit, so setting it here doesn't race with others
+        std::env::set_var("VIBE_MAX_CATEGORIES", "3");
+        let mut manager = manager();
+        let id = add(&mut manager, "busy");
+        let categories = manager.categories.clone();
+        assert!(categories.len() > 3);
+
+        for category in &categories[..3] {
+            assert!(manager.add_category(id, category.clone()).unwrap());
+        }
+        assert!(matches!(manager.add_category(id, categories[3].clone()), Err(TaskError::TooManyCategories(3))));
+        assert!(matches!(manager.set_categories(id, categories.clone()), Err(TaskError::TooManyCategories(3))));
+        assert_eq!(manager.find(id).unwrap().categories.len(), 3);
+
+        manager.set_categories(id, categories[3..4].to_vec()).unwrap();
+        assert_eq!(manager.find(id).unwrap().categories.len(), 1);
+    }
+
+
+    #[test]
+    fn restore_and_unarchive_never_reuse_ids() {
+        let (mut manager, dir) = saved_manager("restore_ids");
+        add(&mut manager, "kept");
+        let backup = manager.backup(Some(dir.join("backup.json"))).unwrap();
+        let id = add(&mut manager, "archived");
+        manager.find_mut(id).unwrap().status = Status::Done;
+        manager.save().unwrap();
+        manager.archive_done().unwrap();
+
+        manager.restore(&backup).unwrap();
+        assert_eq!(add(&mut manager, "new"), id + 1);
+        manager.save().unwrap();
+        assert_eq!(manager.unarchive(id).unwrap(), id);
+
+        // A task that is active again after a restore makes the archived copy take a new id
+        let (mut manager, other) = saved_manager("unarchive_taken_id");
+        let id = add(&mut manager, "both");
+        let backup = manager.backup(Some(other.join("backup.json"))).unwrap();
+        manager.find_mut(id).unwrap().status = Status::Done;
+        manager.save().unwrap();
+        manager.archive_done().unwrap();
+        manager.restore(&backup).unwrap();
+        manager.save().unwrap();
+        let new_id = manager.unarchive(id).unwrap();
+        assert_ne!(new_id, id);
+        assert_eq!(manager.tasks.iter().filter(|t| t.id == id).count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_dir_all(&other).unwrap();
+    }
+
+
+    #[test]
+    fn set_status_updates_existing_tasks_despite_missing_ids() {
+        let mut manager = manager();
+        let id = add(&mut manager, "real");
+        let missing = manager.set_status(&[id, 99], &Status::InProgress);
+        assert!(matches!(missing.as_slice(), [TaskError::NotFound(99)]));
+        assert_eq!(manager.find(id).unwrap().status, Status::InProgress);
+    }
+
+
+    #[test]
+    fn archive_refuses_before_writing_when_the_file_changed_on_disk() {
+        let (mut manager, dir) = saved_manager("archive_changed");
+        let id = add(&mut manager, "done");
+        manager.find_mut(id).unwrap().status = Status::Done;
+        manager.save().unwrap();
+
+        // Another command saves in the meantime
+        let file = fs::File::options().write(true).open(dir.join("tasks.json")).unwrap();
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(60)).unwrap();
+
+        assert!(matches!(manager.archive_done(), Err(TaskError::ChangedOnDisk(_))));
+        assert!(manager.find(id).is_some());
+        assert!(manager.load_archive().unwrap().is_empty());
+
+        manager.ignore_changes_on_disk();
+        assert_eq!(manager.archive_done().unwrap(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+
+    #[test]
+    fn escalation_raises_overdue_tasks_by_one_level() {
+        let mut manager = manager();
+        let now = local(2024, 6, 1, 12, 0);
+        let id = add(&mut manager, "late");
+        manager.find_mut(id).unwrap().priority = Priority::Low;
+        manager.find_mut(id).unwrap().due_date = Some(now - Duration::hours(3));
+
+        let changes = manager.escalate_priorities(Some(24), now);
+        assert_eq!(changes, vec![(id, Priority::Low, Priority::Medium)]);
+        assert!(manager.escalate_priorities(Some(24), now).is_empty());
+    }
+
+
+    #[test]
+    fn humanize_uses_the_largest_whole_unit_and_ignores_the_sign() {
+        assert_eq!(humanize(Duration::seconds(45)), "45s");
+        assert_eq!(humanize(Duration::minutes(-90)), "1h");
+        assert_eq!(humanize(Duration::hours(-30)), "1d");
+        assert_eq!(humanize(Duration::days(15)), "2w");
+    }
+
+
+    #[test]
+    fn undo_after_purge_restores_archived_tasks_too() {
+        let (mut manager, dir) = saved_manager("undo_purge");
+        let archived = add(&mut manager, "archived");
+        manager.find_mut(archived).unwrap().status = Status::Done;
+        manager.save().unwrap();
+        manager.archive_done().unwrap();
+        let mut manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        let active = add(&mut manager, "active");
+        manager.find_mut(active).unwrap().status = Status::Done;
+        manager.save().unwrap();
+
+        let mut manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        assert_eq!(manager.purge(None).unwrap(), 2);
+        let mut manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        manager.undo().unwrap().unwrap();
+
+        let manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        assert!(manager.find(active).is_some());
+        assert_eq!(manager.load_archive().unwrap().iter().map(|t| t.id).collect::<Vec<_>>(), vec![archived]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+
+    #[test]
+    fn undo_after_purging_only_archived_tasks_restores_them() {
+        let (mut manager, dir) = saved_manager("undo_purge_archive_only");
+        let id = add(&mut manager, "archived");
+        manager.find_mut(id).unwrap().status = Status::Done;
+        manager.save().unwrap();
+        manager.archive_done().unwrap();
+
+        let mut manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        assert_eq!(manager.purge(None).unwrap(), 1);
+        let mut manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        manager.undo().unwrap().unwrap();
+
+        let manager = TaskManager::new(dir.join("tasks.json")).unwrap();
+        assert_eq!(manager.load_archive().unwrap().len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+
+    #[test]
+    fn spelled_durations_work_forwards_and_backwards() {
+        let now = local(2024, 6, 5, 12, 0);
+        assert_eq!(parse_past_date_from("3 days ago", now).unwrap(), now - Duration::days(3));
+        assert_eq!(parse_past_date_from("1 week ago", now).unwrap(), now - Duration::weeks(1));
+        assert_eq!(parse_past_date_from("90 mins ago", now).unwrap(), now - Duration::minutes(90));
+        assert_eq!(parse_due_date_from("in 1 week", now, END_OF_DAY).unwrap(), now + Duration::weeks(1));
+        for invalid in ["3 fortnights ago", "few days ago", "2ago"] {
+            assert!(parse_past_date_from(invalid, now).is_err(), "{} should be rejected", invalid);
+        }
+        assert!(parse_due_date_from("in a while", now, END_OF_DAY).is_err());
+    }
+}
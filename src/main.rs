@@ -1,68 +1,64 @@
-use chrono::{DateTime, Duration, Local, NaiveDateTime};  // For date/time handling
-use clap::{Parser, Subcommand};                // For CLI argument parsing
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime}; // For date/time handling
+use clap::{parser::ValueSource, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum}; // For CLI argument parsing
+use clap_complete::Shell;                     // For shell completion scripts
 use colored::*;                                // For terminal colors
-use console::Emoji;                           // For emoji support
-use dialoguer::{Input, MultiSelect, Select};  // For interactive CLI prompts
+use dialoguer::{Confirm, Input, MultiSelect, Select}; // For interactive CLI prompts
 use notify_rust::Notification;                // For system notifications
-use serde::{Deserialize, Serialize};          // For JSON serialization
-use std::{fs, path::PathBuf, thread, time};   // For file system operations and threading
+use serde::Deserialize;                         // For reading task specs piped to add --stdin
+use std::{fs, path::{Path, PathBuf}, process};        // For file system operations and exit codes
+use std::str::FromStr;                          // For parsing enums from names
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc}; // For stopping watch mode on Ctrl-C
+use std::{thread, time};                        // For the watch mode polling loop
+use std::io::{self, IsTerminal, Read, Write};  // For live countdowns and detecting piped output
+use vibe_tasks::*;                              // Tasks, storage and the operations on them
+
+mod email;                                      // Sending reminders over SMTP
+use email::EmailConfig;
 
 // Define emoji constants for consistent usage throughout the app
-static SPARKLES: Emoji<'_, '_> = Emoji("✨ ", "");
-static ROCKET: Emoji<'_, '_> = Emoji("🚀 ", "");
-static CHECKMARK: Emoji<'_, '_> = Emoji("✅ ", "");
-static CALENDAR: Emoji<'_, '_> = Emoji("📅 ", "");
-static FIRE: Emoji<'_, '_> = Emoji("🔥 ", "");
-static CLOCK: Emoji<'_, '_> = Emoji("⏰ ", "");
-static TAG: Emoji<'_, '_> = Emoji("🏷️ ", "");
-
-// Category represents a task category with associated color and emoji
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-struct Category {
-    name: String,
-    color: String,
-    emoji: String,
-}
-
-// TimeEntry represents a single time tracking session
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct TimeEntry {
-    start_time: DateTime<Local>,
-    end_time: Option<DateTime<Local>>,
-    duration: Option<Duration>,
-}
-
-// Task struct represents a single task in the system
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct Task {
-    id: usize,                                // Unique identifier for the task
-    title: String,                            // Task title
-    description: Option<String>,              // Optional task description
-    priority: Priority,                       // Task priority level
-    status: Status,                           // Current task status
-    due_date: Option<DateTime<Local>>,        // Optional due date
-    created_at: DateTime<Local>,              // Creation timestamp
-    categories: Vec<Category>,                // Task categories/tags
-    time_entries: Vec<TimeEntry>,             // Time tracking entries
-    current_time_entry: Option<TimeEntry>,    // Currently running time entry
-    last_notification: Option<DateTime<Local>>, // Last notification sent
-}
-
-// Priority enum defines possible priority levels for tasks
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-enum Priority {
-    Low,
-    Medium,
-    High,
-    Urgent,
-}
-
-// Status enum defines possible states for a task
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
-enum Status {
-    Todo,
-    InProgress,
-    Done,
+static SPARKLES: Emoji = Emoji("✨ ", "");
+static ROCKET: Emoji = Emoji("🚀 ", "");
+static CHECKMARK: Emoji = Emoji("✅ ", "");
+static CALENDAR: Emoji = Emoji("📅 ", "");
+static FIRE: Emoji = Emoji("🔥 ", "");
+static CLOCK: Emoji = Emoji("⏰ ", "");
+static TAG: Emoji = Emoji("🏷️ ", "");
+static TRACKING: Emoji = Emoji("🔄 ", "");
+static PAUSED: Emoji = Emoji("⏸️ ", "");
+static TIMER: Emoji = Emoji("⏱️ ", "");
+static WARNING: Emoji = Emoji("⚠️ ", "");
+static BLOCKED: Emoji = Emoji("⛔ ", "");
+static PERSON: Emoji = Emoji("👤 ", "");
+
+// Exit status of check-notifications when at least one reminder was sent
+const NOTIFIED_EXIT_CODE: i32 = 10;
+
+// Bulk changes touching more tasks than this ask for confirmation first
+const BULK_CONFIRM_THRESHOLD: usize = 5;
+
+// Set by --quiet to silence success messages
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+// Prints a success message unless --quiet was given
+macro_rules! success {
+    ($($arg:tt)*) => {
+        if !QUIET.load(Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
+
+// Emoji with a plain-text fallback, used whenever colored output is turned off
+struct Emoji(&'static str, &'static str);
+
+impl std::fmt::Display for Emoji {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if colored::control::SHOULD_COLORIZE.should_colorize() {
+            write!(f, "{}", self.0)
+        } else {
+            write!(f, "{}", self.1)
+        }
+    }
 }
 
 // CLI struct for parsing command line arguments
@@ -73,446 +69,2836 @@ enum Status {
     version = "1.0"
 )]
 struct Cli {
+    #[arg(long, global = true, env = "VIBE_TASKS_FILE", help = "Tasks file to use (default: ~/.vibe_tasks.json)")]
+    file: Option<PathBuf>,
+    #[arg(long, global = true, env = "VIBE_TASKS_LIST", help = "Named task list to use, stored in ~/.vibe_tasks/<name>.json")]
+    list: Option<String>,
+    #[arg(long, global = true, value_enum, default_value = "text", help = "Output format")]
+    format: OutputFormat,
+    #[arg(long, global = true, help = "Disable colors and emoji (also via NO_COLOR, or when output isn't a terminal)")]
+    no_color: bool,
+    #[arg(long, short, global = true, help = "Don't print success messages")]
+    quiet: bool,
     #[command(subcommand)]
     command: Commands,
 }
 
+// Output formats for commands that print task data
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum OutputFormat {
+    #[value(help = "Colorful human-readable output")]
+    Text,
+    #[value(help = "Machine-readable JSON")]
+    Json,
+}
+
+// Ways of delivering due-date reminders
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum NotifyVia {
+    #[value(help = "Desktop notification")]
+    Desktop,
+    #[value(help = "Email over SMTP, configured with the VIBE_SMTP_* environment variables")]
+    Email,
+}
+
 // Enum defining all available CLI commands
 #[derive(Subcommand)]
 enum Commands {
     #[command(about = "Add a new task")]
-    Add,
+    Add(AddArgs),
     #[command(about = "List all tasks")]
-    List,
-    #[command(about = "Mark a task as complete")]
-    Complete { id: usize },
-    #[command(about = "Update task status")]
-    Status { id: usize },
-    #[command(about = "Delete a task")]
-    Delete { id: usize },
+    List(ListArgs),
+    #[command(visible_alias = "done", about = "Mark one or more tasks as complete")]
+    Complete {
+        #[arg(help = "Tasks to complete, by id or part of the title (pick from a menu if omitted)")]
+        ids: Vec<String>,
+        #[arg(long, help = "Complete even if the task is blocked by unfinished dependencies")]
+        force: bool,
+        #[arg(long, help = "Closing note to add to each completed task")]
+        note: Option<String>,
+    },
+    #[command(about = "Reopen a completed task")]
+    Reopen {
+        id: usize,
+        #[arg(long, help = "Reopen as Todo instead of In Progress")]
+        todo: bool,
+    },
+    #[command(about = "Update the status of one or more tasks")]
+    Status {
+        #[arg(required = true, help = "Tasks to update, by id or part of the title")]
+        ids: Vec<String>,
+    },
+    #[command(about = "Set the status of every task in a category or with a priority")]
+    #[command(group = clap::ArgGroup::new("filter").required(true).multiple(true).args(["category", "priority"]))]
+    SetStatus {
+        #[arg(value_parser = Status::from_str, help = "New status: todo, in-progress or done")]
+        status: Status,
+        #[arg(long, help = "Only tasks in this category")]
+        category: Option<String>,
+        #[arg(long, value_parser = Priority::from_str, help = "Only tasks with this priority")]
+        priority: Option<Priority>,
+        #[arg(long, short, help = "Don't ask for confirmation")]
+        yes: bool,
+    },
+    #[command(about = "Edit a task's title, description, priority and due date")]
+    Edit { id: usize },
+    #[command(about = "Delete one or more tasks")]
+    Delete {
+        #[arg(help = "Tasks to delete, by id or part of the title (pick from a menu if omitted)")]
+        ids: Vec<String>,
+        #[arg(long, short, help = "Don't ask for confirmation")]
+        yes: bool,
+    },
     #[command(about = "Add categories to a task")]
     AddCategories { id: usize },
+    #[command(about = "Define a new category")]
+    CategoryAdd {
+        name: String,
+        #[arg(long, default_value = "white", help = "Display color (e.g. blue, green, red)")]
+        color: String,
+        #[arg(long, default_value = "🏷️", help = "Emoji shown next to the name")]
+        emoji: String,
+    },
+    #[command(about = "List the available categories")]
+    CategoryList,
+    #[command(about = "Remove a category definition")]
+    CategoryRemove { name: String },
+    #[command(about = "Add a category to a task by name")]
+    CategoryAssign { id: usize, name: String },
+    #[command(about = "Add a category to every task whose title contains some text")]
+    Categorize {
+        #[arg(long = "match", value_name = "TEXT", help = "Text to look for in task titles (ignoring case)")]
+        text: String,
+        category: String,
+        #[arg(long, short, help = "Don't ask for confirmation")]
+        yes: bool,
+    },
+    #[command(about = "Add free-form tags to a task, like q3 or waiting")]
+    Tag {
+        id: usize,
+        #[arg(required = true)]
+        tags: Vec<String>,
+        #[arg(long, help = "Remove these tags instead")]
+        remove: bool,
+    },
     #[command(about = "Start time tracking for a task")]
-    StartTime { id: usize },
+    StartTime {
+        #[arg(help = "Task to track, by id or part of the title (pick from a menu if omitted)")]
+        id: Option<String>,
+        #[arg(long, help = "Start even if the task is already done")]
+        force: bool,
+        #[arg(long, help = "Stop the timer running on another task first")]
+        switch: bool,
+    },
     #[command(about = "Stop time tracking for a task")]
     StopTime { id: usize },
+    #[command(about = "Discard a running timer without recording the time")]
+    ClearTimer {
+        id: usize,
+        #[arg(long, short, help = "Don't ask for confirmation")]
+        yes: bool,
+    },
+    #[command(about = "Pause time tracking for a task")]
+    PauseTime { id: usize },
+    #[command(about = "Resume paused time tracking for a task")]
+    ResumeTime { id: usize },
+    #[command(about = "Adjust or delete a recorded time tracking session")]
+    EditTime { id: usize },
     #[command(about = "Show time tracking summary for a task")]
-    TimeReport { id: usize },
+    TimeReport {
+        id: usize,
+        #[arg(long, help = "Show durations as decimal hours")]
+        hours: bool,
+        #[arg(long, value_parser = parse_date, help = "Only include sessions started on or after this date (YYYY-MM-DD)")]
+        since: Option<NaiveDate>,
+        #[arg(long, value_parser = parse_date, help = "Only include sessions started on or before this date (YYYY-MM-DD)")]
+        until: Option<NaiveDate>,
+    },
+    #[command(about = "Add a checklist item to a task")]
+    AddSubtask {
+        id: usize,
+        #[arg(long, help = "Subtask title (prompted for if omitted)")]
+        title: Option<String>,
+    },
+    #[command(about = "Mark a task as blocked until another task is done")]
+    DependsOn {
+        id: usize,
+        on: usize,
+        #[arg(long, help = "Remove the dependency instead")]
+        remove: bool,
+    },
+    #[command(about = "Set how far along a task is, in percent")]
+    Progress {
+        id: usize,
+        #[arg(allow_negative_numbers = true, help = "Percent complete (clamped to 0-100)")]
+        percent: i64,
+    },
+    #[command(about = "Work on a task in timed focus blocks with breaks in between")]
+    Pomodoro {
+        id: usize,
+        #[arg(long, default_value_t = 25, value_parser = clap::value_parser!(u64).range(1..), help = "Minutes per focus block")]
+        work: u64,
+        #[arg(long = "break", default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..), help = "Minutes per break")]
+        rest: u64,
+    },
+    #[command(about = "Move a task up or down the hand-curated order used by list --sort rank")]
+    #[command(group = clap::ArgGroup::new("position").required(true).args(["up", "down", "to"]))]
+    Move {
+        id: usize,
+        #[arg(long, help = "One place up")]
+        up: bool,
+        #[arg(long, help = "One place down")]
+        down: bool,
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..), help = "To this rank (1 is the top)")]
+        to: Option<u64>,
+    },
+    #[command(about = "Create a copy of a task as a new Todo")]
+    Duplicate { id: usize },
+    #[command(about = "Save a task's title, details, categories and checklist as a named template")]
+    TemplateSave { id: usize, name: String },
+    #[command(about = "Create a new task from a saved template")]
+    TemplateApply {
+        name: String,
+        #[arg(long, value_parser = parse_due_date, help = "Due date for the new task")]
+        due: Option<DateTime<Local>>,
+    },
+    #[command(about = "Show the saved task templates")]
+    TemplateList,
+    #[command(about = "Push a task's due date back, e.g. by 1d, 3h or 1w")]
+    Snooze {
+        id: usize,
+        #[arg(value_parser = parse_duration)]
+        duration: Duration,
+    },
+    #[command(about = "Show everything recorded for one task")]
+    Show { id: usize },
+    #[command(about = "Append a timestamped progress note to a task")]
+    Note {
+        id: usize,
+        #[arg(long, help = "Note text (prompted for if omitted)")]
+        text: Option<String>,
+    },
+    #[command(about = "Check or uncheck a task's checklist item")]
+    ToggleSubtask { id: usize, index: usize },
+    #[command(about = "Export tasks for sharing elsewhere")]
+    Export(ExportArgs),
+    #[command(about = "Set or clear (by omitting the rate) a task's hourly billing rate")]
+    SetRate { id: usize, rate: Option<f64> },
+    #[command(about = "Set or clear (by omitting the hours) how long before its due date a task's priority rises")]
+    SetEscalation { id: usize, hours: Option<i64> },
+    #[command(about = "Raise the priority of unfinished tasks nearing their due date")]
+    Escalate,
+    #[command(about = "Set or clear (by omitting the minutes) a task's weekly time goal")]
+    SetGoal { id: usize, minutes: Option<u32> },
+    #[command(about = "Summarize billable hours and cost, optionally for one category")]
+    Invoice {
+        #[arg(long, help = "Only include tasks in this category")]
+        category: Option<String>,
+        #[arg(long, value_parser = parse_date, help = "Only include sessions started on or after this date (YYYY-MM-DD)")]
+        since: Option<NaiveDate>,
+        #[arg(long, value_parser = parse_date, help = "Only include sessions started on or before this date (YYYY-MM-DD)")]
+        until: Option<NaiveDate>,
+    },
+    #[command(about = "Show tracked hours per category across all tasks")]
+    CategoryTime {
+        #[arg(long, value_parser = parse_date, help = "Only include sessions started on or after this date (YYYY-MM-DD)")]
+        since: Option<NaiveDate>,
+    },
+    #[command(about = "Show today's agenda: tasks due today plus anything overdue")]
+    Today,
+    #[command(about = "Suggest the most important task to work on next")]
+    Next,
+    #[command(about = "Show tracked time per day for a week across all tasks")]
+    Timesheet {
+        #[arg(long, default_value_t = 0, allow_negative_numbers = true, help = "Which week: 0 for this week, -1 for last week, ...")]
+        week: i64,
+    },
+    #[command(about = "Show an overview of all tasks")]
+    Stats {
+        #[arg(long, value_name = "HOURS", help = "Hours you have available, to check outstanding estimates against")]
+        available: Option<f64>,
+    },
+    #[command(about = "Move all completed tasks to the archive")]
+    Archive,
+    #[command(about = "Move a task from the archive back to the active list")]
+    Unarchive { id: usize },
+    #[command(about = "Permanently delete completed tasks, including archived ones")]
+    Purge {
+        #[arg(long, value_parser = parse_date, help = "Only purge tasks completed before this date (YYYY-MM-DD)")]
+        before: Option<NaiveDate>,
+        #[arg(long, short, help = "Don't ask for confirmation")]
+        yes: bool,
+    },
+    #[command(about = "Undo the last command that changed tasks")]
+    Undo,
+    #[command(about = "Search task titles, descriptions and categories")]
+    Search { query: String },
     #[command(about = "Check for due tasks and send notifications")]
-    CheckNotifications,
+    CheckNotifications {
+        #[arg(long, value_enum, default_value = "desktop", help = "How to deliver reminders")]
+        notify: NotifyVia,
+    },
+    #[command(about = "Keep running and check for due tasks periodically")]
+    Watch {
+        #[arg(long, default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..), help = "Seconds between checks")]
+        interval: u64,
+        #[arg(long, value_enum, default_value = "desktop", help = "How to deliver reminders")]
+        notify: NotifyVia,
+    },
+    #[command(about = "Save a full copy of the tasks file, by default into a timestamped backup")]
+    Backup {
+        #[arg(long, help = "Write the backup here instead of the backups folder (skips pruning)")]
+        path: Option<PathBuf>,
+    },
+    #[command(about = "Replace all tasks with those from a backup file")]
+    Restore {
+        path: PathBuf,
+        #[arg(long, short, help = "Don't ask for confirmation")]
+        yes: bool,
+    },
+    #[command(about = "Point out planning problems, like urgent tasks without a due date")]
+    Lint,
+    #[command(about = "Show the available task lists and how many tasks each has")]
+    Lists,
+    #[command(about = "Make a named list the default for future commands")]
+    SwitchList {
+        #[arg(help = "List name (\"default\" for the original unnamed list)")]
+        name: String,
+    },
+    #[command(about = "Show or set how far ahead new tasks of a priority are due when added without a due date")]
+    DueOffset {
+        #[arg(value_parser = Priority::from_str, requires = "offset_or_clear", help = "Priority to configure (omit to show all offsets)")]
+        priority: Option<Priority>,
+        #[arg(group = "offset_or_clear", help = "Offset from now, e.g. 4h, 1d or 2w")]
+        offset: Option<String>,
+        #[arg(long, group = "offset_or_clear", help = "Stop filling in a due date for this priority")]
+        clear: bool,
+    },
+    #[command(about = "Print a shell completion script (bash, zsh, fish, powershell)")]
+    Completions { shell: Shell },
 }
 
-// TaskManager handles all task-related operations and storage
-struct TaskManager {
-    tasks: Vec<Task>,
-    file_path: PathBuf,
+// Optional flags for creating a task without going through every prompt
+#[derive(Args)]
+struct AddArgs {
+    #[arg(long, help = "Task title")]
+    title: Option<String>,
+    #[arg(long, help = "Task description")]
+    description: Option<String>,
+    #[arg(long, value_parser = Priority::from_str, help = "Priority: low, medium, high or urgent")]
+    priority: Option<Priority>,
+    #[arg(long, value_parser = parse_due_date, help = "Due date (YYYY-MM-DD [HH:MM], or e.g. tomorrow, today 17:00, +3d, next monday, in 2 hours)")]
+    due: Option<DateTime<Local>>,
+    #[arg(long, value_parser = parse_recurrence, help = "Repeat after completion: daily, weekly or every N days (e.g. 3d)")]
+    repeat: Option<Recurrence>,
+    #[arg(long, help = "Hourly rate for billing tracked time")]
+    rate: Option<f64>,
+    #[arg(long, value_name = "HOURS", help = "Start reminding this many hours before the due date")]
+    notify_before: Option<i64>,
+    #[arg(long, value_name = "HOURS", help = "Raise the priority this many hours before the due date (see escalate)")]
+    escalate_within: Option<i64>,
+    #[arg(long = "category", value_name = "NAME", help = "Assign a category (repeatable; case and emoji don't matter)")]
+    categories: Vec<String>,
+    #[arg(long, help = "Who the task belongs to")]
+    assignee: Option<String>,
+    #[arg(long, value_name = "MINUTES", help = "How long you expect the task to take")]
+    estimate: Option<u32>,
+    #[arg(long, help = "Pick categories right after adding the task")]
+    categorize: bool,
+    #[arg(long, help = "Don't check for an existing task with the same title and due date")]
+    allow_dup: bool,
+    #[arg(long, alias = "json-input", conflicts_with = "title", help = "Read the task as a JSON object from stdin, without prompts")]
+    stdin: bool,
+    #[arg(long, requires = "title", help = "Don't prompt for fields not given as flags (the default when stdin isn't a terminal)")]
+    no_prompt: bool,
 }
 
-impl TaskManager {
-    // Creates a new TaskManager instance
-    fn new() -> Self {
-        let home_dir = dirs::home_dir().expect("Could not find home directory");
-        let file_path = home_dir.join(".vibe_tasks.json");
-        
-        let tasks = if file_path.exists() {
-            let data = fs::read_to_string(&file_path).expect("Failed to read tasks file");
-            serde_json::from_str(&data).unwrap_or_default()
-        } else {
-            Vec::new()
-        };
-
-        TaskManager { tasks, file_path }
-    }
-
-    // Saves current tasks to the JSON file
-    fn save(&self) {
-        let data = serde_json::to_string_pretty(&self.tasks).expect("Failed to serialize tasks");
-        fs::write(&self.file_path, data).expect("Failed to save tasks");
-    }
-
-    // Adds categories to a task
-    fn add_categories(&mut self, id: usize) {
-        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
-            // Predefined categories with colors and emojis
-            let available_categories = vec![
-                Category {
-                    name: "Work".to_string(),
-                    color: "blue".to_string(),
-                    emoji: "💼".to_string(),
-                },
-                Category {
-                    name: "Personal".to_string(),
-                    color: "green".to_string(),
-                    emoji: "🏠".to_string(),
-                },
-                Category {
-                    name: "Study".to_string(),
-                    color: "yellow".to_string(),
-                    emoji: "📚".to_string(),
-                },
-                Category {
-                    name: "Health".to_string(),
-                    color: "red".to_string(),
-                    emoji: "💪".to_string(),
-                },
-                Category {
-                    name: "Shopping".to_string(),
-                    color: "cyan".to_string(),
-                    emoji: "🛒".to_string(),
-                },
-            ];
-
-            let category_names: Vec<String> = available_categories
-                .iter()
-                .map(|c| format!("{} {}", c.emoji, c.name))
-                .collect();
-
-            let selections = MultiSelect::new()
-                .with_prompt(format!("{} Select categories", TAG))
-                .items(&category_names)
-                .interact()
-                .unwrap();
+// TaskSpec is the JSON object read by add --stdin. Field names follow the add flags;
+// the names used by --format json output are accepted too, and other fields are ignored.
+#[derive(Deserialize)]
+struct TaskSpec {
+    title: String,
+    description: Option<String>,
+    priority: Option<String>,
+    #[serde(alias = "due_date")]
+    due: Option<String>,
+    repeat: Option<String>,
+    #[serde(alias = "hourly_rate")]
+    rate: Option<f64>,
+    notify_before: Option<i64>,
+    #[serde(default)]
+    categories: Vec<String>,
+    assignee: Option<String>,
+    #[serde(alias = "estimated_minutes")]
+    estimate: Option<u32>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
 
-            task.categories = selections
-                .iter()
-                .map(|&i| available_categories[i].clone())
-                .collect();
+// Options for narrowing down which tasks get listed or exported
+#[derive(Args)]
+struct FilterArgs {
+    #[arg(long, value_parser = Status::from_str, help = "Only include tasks with this status: todo, in-progress or done")]
+    status: Option<Status>,
+    #[arg(long, value_parser = Priority::from_str, help = "Only include tasks with this priority")]
+    priority: Option<Priority>,
+    #[arg(long, help = "Only include tasks assigned to this person")]
+    assignee: Option<String>,
+    #[arg(long, help = "Only include tasks with this tag")]
+    tag: Option<String>,
+    #[arg(long, value_parser = parse_past_date, allow_hyphen_values = true, help = "Only include tasks created on or after this date (e.g. 2024-06-01, yesterday, last monday, -7d)")]
+    created_after: Option<DateTime<Local>>,
+    #[arg(long, value_parser = parse_past_date, allow_hyphen_values = true, help = "Only include tasks created before this date")]
+    created_before: Option<DateTime<Local>>,
+}
 
-            self.save();
-            println!("{} Categories updated!", CHECKMARK);
-        } else {
-            println!("Task not found!");
+impl FilterArgs {
+    // The library filter matching these options
+    fn to_filter(&self) -> TaskFilter {
+        TaskFilter {
+            status: self.status.clone(),
+            priority: self.priority.clone(),
+            assignee: self.assignee.clone(),
+            tag: self.tag.clone(),
+            created_after: self.created_after,
+            created_before: self.created_before,
         }
     }
+}
 
-    // Starts time tracking for a task
-    fn start_time_tracking(&mut self, id: usize) {
-        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
-            if task.current_time_entry.is_some() {
-                println!("Time tracking is already running for this task!");
-                return;
-            }
+// Options for the list command
+#[derive(Args)]
+struct ListArgs {
+    #[command(flatten)]
+    filter: FilterArgs,
+    #[arg(long, value_enum, help = "Order the listed tasks")]
+    sort: Option<SortKey>,
+    #[arg(long, help = "List archived tasks instead of active ones")]
+    archived: bool,
+    #[arg(long, help = "Show tracked time as decimal hours")]
+    hours: bool,
+    #[arg(long, default_value_t = 0, help = "Show at most this many tasks (0 for all)")]
+    limit: usize,
+    #[arg(long, help = "Only list unfinished tasks past their due date, most overdue first")]
+    overdue: bool,
+    #[arg(long, help = "Show due and creation dates as timestamps instead of e.g. \"in 3h\" or \"2d ago\"")]
+    absolute: bool,
+    #[arg(long, help = "One line per task: id, priority, status, title, due date and category emojis")]
+    compact: bool,
+    #[arg(long, value_enum, default_value = "plain", help = "How to color each task")]
+    theme: Theme,
+}
 
-            let time_entry = TimeEntry {
-                start_time: Local::now(),
-                end_time: None,
-                duration: None,
-            };
+// Options for the export command
+#[derive(Args)]
+struct ExportArgs {
+    #[arg(value_enum, value_name = "FORMAT")]
+    kind: ExportFormat,
+    #[arg(long, short, help = "Write to this file instead of stdout")]
+    output: Option<PathBuf>,
+    #[arg(long, value_parser = parse_duration, help = "ics only: add an alarm this long before each due date (e.g. 1h, 30m)")]
+    reminder: Option<Duration>,
+    #[command(flatten)]
+    filter: FilterArgs,
+}
 
-            task.current_time_entry = Some(time_entry);
-            self.save();
-            println!("{} Time tracking started!", CLOCK);
-        } else {
-            println!("Task not found!");
-        }
+// File formats supported by the export command
+#[derive(Clone, ValueEnum)]
+enum ExportFormat {
+    #[value(help = "Checklist grouped by status, for pasting into notes or issues")]
+    Markdown,
+    #[value(help = "iCalendar events for tasks with a due date")]
+    Ics,
+}
+
+// Orderings available for the task list
+#[derive(Clone, ValueEnum)]
+enum SortKey {
+    #[value(help = "Urgent first, down to Low")]
+    Priority,
+    #[value(help = "Nearest due date first, tasks without one last")]
+    Due,
+    #[value(help = "Hand-curated order set with the move command")]
+    Rank,
+}
+
+// Color schemes for listed tasks
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Theme {
+    #[value(help = "The usual colors")]
+    Plain,
+    #[value(help = "Tint the separator and title in the color of the task's category, if it has exactly one")]
+    Category,
+}
+
+// Priority for new tasks when none is chosen, from VIBE_DEFAULT_PRIORITY (Low if unset)
+fn default_priority() -> Priority {
+    match std::env::var("VIBE_DEFAULT_PRIORITY") {
+        Ok(value) => value.parse().unwrap_or_else(|e| {
+            eprintln!("{} Ignoring VIBE_DEFAULT_PRIORITY: {}", "Warning:".yellow().bold(), e);
+            Priority::Low
+        }),
+        Err(_) => Priority::Low,
     }
+}
 
-    // Stops time tracking for a task
-    fn stop_time_tracking(&mut self, id: usize) {
-        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
-            if let Some(mut current_entry) = task.current_time_entry.take() {
-                let end_time = Local::now();
-                current_entry.end_time = Some(end_time);
-                current_entry.duration = Some(end_time - current_entry.start_time);
-                task.time_entries.push(current_entry);
-                self.save();
-                println!("{} Time tracking stopped!", CLOCK);
+// Prompts for an optional due date, re-asking until the input parses or is left empty
+fn prompt_due_date(current: Option<DateTime<Local>>) -> Option<DateTime<Local>> {
+    let input: String = Input::new()
+        .with_prompt(format!("{} Due date (YYYY-MM-DD [HH:MM], tomorrow, +3d..., optional)", CALENDAR))
+        .with_initial_text(current.map(|due| due.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default())
+        .allow_empty(true)
+        .validate_with(|input: &String| {
+            if input.is_empty() {
+                Ok(())
             } else {
-                println!("No active time tracking for this task!");
+                parse_due_date(input).map(|_| ())
             }
-        } else {
-            println!("Task not found!");
+        })
+        .interact_text()
+        .unwrap();
+
+    if input.is_empty() {
+        None
+    } else {
+        parse_due_date(&input).ok()
+    }
+}
+
+// Prompts for a local "YYYY-MM-DD HH:MM:SS" timestamp, pre-filled with the current value
+fn prompt_timestamp(label: &str, current: DateTime<Local>) -> DateTime<Local> {
+    let format = "%Y-%m-%d %H:%M:%S";
+    let input: String = Input::new()
+        .with_prompt(format!("{} (YYYY-MM-DD HH:MM:SS)", label))
+        .with_initial_text(current.format(format).to_string())
+        .validate_with(|input: &String| {
+            NaiveDateTime::parse_from_str(input, format)
+                .map(|_| ())
+                .map_err(|_| "Expected YYYY-MM-DD HH:MM:SS")
+        })
+        .interact_text()
+        .unwrap();
+
+    local_from_naive(NaiveDateTime::parse_from_str(&input, format).unwrap())
+}
+
+// Prints each task list with its task count, marking the current default
+fn print_lists() -> Result<(), String> {
+    let current = Config::load()?.default_list.unwrap_or_else(|| DEFAULT_LIST.to_string());
+    for (name, count) in TaskManager::lists()? {
+        let marker = if name == current { "*" } else { " " };
+        let noun = if count == 1 { "task" } else { "tasks" };
+        println!("{} {} ({} {})", marker.green().bold(), name.bold(), count, noun);
+    }
+    Ok(())
+}
+
+// Remembers a list as the default for commands run without --list
+fn switch_list(name: &str) -> Result<(), String> {
+    // Validates the name before it's saved
+    TaskManager::list_path(name)?;
+    let mut config = Config::load()?;
+    config.default_list = (name != DEFAULT_LIST).then(|| name.to_string());
+    config.save()?;
+    success!("{} Switched to list '{}'", CHECKMARK, name);
+    Ok(())
+}
+
+// Turns ids or title fragments from the command line into task ids, along with an error for
+// every title that matches no task or several. Unknown ids are passed on for the command to report.
+fn resolve_ids(manager: &TaskManager, selectors: &[String]) -> (Vec<usize>, Vec<String>) {
+    let mut ids = Vec::new();
+    let mut errors = Vec::new();
+    for selector in selectors {
+        match manager.resolve_task(selector) {
+            Ok(task) => ids.push(task.id),
+            Err(TaskError::NotFound(id)) => ids.push(id),
+            Err(e) => errors.push(e.to_string()),
         }
     }
+    (ids, errors)
+}
 
-    // Generates a time report for a task
-    fn generate_time_report(&self, id: usize) {
-        if let Some(task) = self.tasks.iter().find(|t| t.id == id) {
-            println!("\n{}", "=".repeat(50).cyan());
-            println!("Time Report for Task #{}: {}", task.id, task.title.bold());
-            
-            if task.time_entries.is_empty() {
-                println!("No time entries recorded for this task.");
-                return;
-            }
+// Runs a command on the tasks the selectors resolve to, then reports the selectors that
+// didn't resolve; one bad selector doesn't stop the rest
+fn with_selected_ids(
+    manager: &mut TaskManager,
+    selectors: &[String],
+    command: impl FnOnce(&mut TaskManager, &[usize]) -> Result<(), String>,
+) -> Result<(), String> {
+    let (ids, mut errors) = resolve_ids(manager, selectors);
+    if !ids.is_empty() {
+        if let Err(e) = command(manager, &ids) {
+            errors.insert(0, e);
+        }
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors.join("\n")) }
+}
 
-            let mut total_duration = Duration::zero();
-            for (i, entry) in task.time_entries.iter().enumerate() {
-                if let Some(duration) = entry.duration {
-                    total_duration = total_duration + duration;
-                    println!("\nSession {}:", i + 1);
-                    println!("Start: {}", entry.start_time.format("%Y-%m-%d %H:%M:%S"));
-                    if let Some(end) = entry.end_time {
-                        println!("End: {}", end.format("%Y-%m-%d %H:%M:%S"));
-                    }
-                    println!("Duration: {:.2} hours", duration.num_minutes() as f64 / 60.0);
-                }
-            }
+// Shows the due date offsets per priority, or sets or clears the one for a priority
+fn due_offset(priority: Option<Priority>, offset: Option<&str>, clear: bool) -> Result<(), String> {
+    let mut config = Config::load()?;
+    let Some(priority) = priority else {
+        if config.due_offsets.is_empty() {
+            println!("No due date offsets set; tasks added without a due date have none.");
+        }
+        for (priority, offset) in &config.due_offsets {
+            println!("{}: +{}", priority, offset);
+        }
+        return Ok(());
+    };
+
+    if clear {
+        config.due_offsets.remove(&priority);
+        config.save()?;
+        success!("{} {} tasks no longer get a due date automatically", CHECKMARK, priority);
+        return Ok(());
+    }
+    let offset = offset.unwrap_or_default().trim().trim_start_matches('+').to_lowercase();
+    parse_duration(&offset)?;
+    config.due_offsets.insert(priority.clone(), offset.clone());
+    config.save()?;
+    success!("{} {} tasks added without a due date will be due in {}", CALENDAR, priority, offset);
+    Ok(())
+}
+
+// Lets the user choose a task from a menu when no id was given on the command line
+fn pick_task(manager: &TaskManager, prompt: &str, relevant: impl Fn(&Task) -> bool) -> Result<usize, String> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err("No task id given; pass one explicitly when not running interactively.".to_string());
+    }
+    let candidates: Vec<&Task> = manager.tasks.iter().filter(|t| relevant(t)).collect();
+    if candidates.is_empty() {
+        return Err("No matching tasks to choose from.".to_string());
+    }
+
+    let names: Vec<String> = candidates.iter().map(|t| format!("#{} {}", t.id, t.title)).collect();
+    let task_idx = Select::new()
+        .with_prompt(prompt)
+        .items(&names)
+        .default(0)
+        .interact()
+        .map_err(|e| e.to_string())?;
+    Ok(candidates[task_idx].id)
+}
+
+// Reads a whole number from an environment variable, falling back to the default if unset or invalid
+fn env_number(name: &str, default: i64) -> i64 {
+    match std::env::var(name) {
+        Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+            eprintln!("{} Ignoring invalid {}='{}'", "Warning:".yellow().bold(), name, value);
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+// Warning for a running timer that has gone past VIBE_TIMER_CAP_HOURS (off unless set)
+fn forgotten_timer_warning(task: &Task, now: DateTime<Local>) -> Option<String> {
+    let cap = env_number("VIBE_TIMER_CAP_HOURS", 0);
+    let elapsed = now - task.current_time_entry.as_ref()?.start_time;
+    (cap > 0 && elapsed > Duration::hours(cap)).then(|| {
+        format!(
+            "{}Timer running for {}h — probably forgotten? Stop it and fix the session with edit-time {}",
+            WARNING,
+            elapsed.num_hours(),
+            task.id
+        )
+    })
+}
+
+// Returns a flag that flips to false once the user presses Ctrl-C
+fn stop_on_ctrlc() -> Result<Arc<AtomicBool>, String> {
+    let running = Arc::new(AtomicBool::new(true));
+    let handler_flag = Arc::clone(&running);
+    ctrlc::set_handler(move || handler_flag.store(false, Ordering::SeqCst))
+        .map_err(|e| format!("Could not install Ctrl-C handler: {}", e))?;
+    Ok(running)
+}
+
+// Shows a live mm:ss countdown, returning false if interrupted before it ran out
+fn countdown(label: &str, seconds: u64, running: &AtomicBool) -> bool {
+    for left in (1..=seconds).rev() {
+        if !running.load(Ordering::SeqCst) {
+            println!();
+            return false;
+        }
+        print!("\r{} {:02}:{:02} remaining ", label, left / 60, left % 60);
+        let _ = io::stdout().flush();
+        thread::sleep(time::Duration::from_secs(1));
+    }
+    println!("\r{} done!            ", label);
+    running.load(Ordering::SeqCst)
+}
+
+// Shows a desktop notification, reporting failures instead of aborting
+fn send_notification(summary: &str, body: &str) {
+    if let Err(e) = Notification::new().summary(summary).body(body).icon("calendar").show() {
+        eprintln!("Failed to send notification: {}", e);
+    }
+}
+
+// Reminder headline, more insistent the higher the task's priority
+fn reminder_summary(priority: &Priority) -> &'static str {
+    match priority {
+        Priority::Low => "Task due soon",
+        Priority::Medium => "Task Due Soon!",
+        Priority::High => "⚠️ Important Task Due Soon!",
+        Priority::Urgent => "🚨 URGENT TASK DUE!",
+    }
+}
+
+// Builds the desktop reminder for a task, with an urgency hint matching its priority
+fn due_notification(priority: &Priority, text: &str) -> Notification {
+    let mut notification = Notification::new();
+    notification.summary(reminder_summary(priority)).body(text).icon("calendar");
+    // macOS only supports urgency through an optional notify-rust feature
+    #[cfg(not(target_os = "macos"))]
+    notification.urgency(match priority {
+        Priority::Low => notify_rust::Urgency::Low,
+        Priority::Medium | Priority::High => notify_rust::Urgency::Normal,
+        Priority::Urgent => notify_rust::Urgency::Critical,
+    });
+    notification
+}
+
+// Returns the colored label used when displaying a status, with a symbol so it
+// doesn't rely on color alone
+fn colored_status(status: &Status) -> ColoredString {
+    status_style(&format!("{} {}", status_symbol(status), status.to_string().to_uppercase()), status)
+}
+
+// The circle shown before a status, filling up as the task progresses
+fn status_symbol(status: &Status) -> &'static str {
+    match status {
+        Status::Todo => "○",
+        Status::InProgress => "◐",
+        Status::Done => "●",
+    }
+}
+
+// Colors any text the way its status is shown
+fn status_style(text: &str, status: &Status) -> ColoredString {
+    match status {
+        Status::Todo => text.red(),
+        Status::InProgress => text.yellow(),
+        Status::Done => text.green(),
+    }
+}
+
+// Returns the colored label used when displaying a priority
+fn colored_priority(priority: &Priority) -> ColoredString {
+    priority_style(&priority.to_string().to_uppercase(), priority)
+}
+
+// Colors any text the way its priority is shown
+fn priority_style(text: &str, priority: &Priority) -> ColoredString {
+    match priority {
+        Priority::Low => text.blue(),
+        Priority::Medium => text.yellow(),
+        Priority::High => text.red(),
+        Priority::Urgent => text.red().bold(),
+    }
+}
+
+// The color to tint a task with under the theme: its category's, when it has exactly one
+// category with a known color
+fn task_tint(task: &Task, theme: Theme) -> Option<Color> {
+    match (theme, task.categories.as_slice()) {
+        (Theme::Category, [category]) => category.color.parse().ok(),
+        _ => None,
+    }
+}
+
+// Returns a category's emoji and name in its configured color, or uncolored if the name is unknown
+fn colored_category(category: &Category) -> ColoredString {
+    let label = format!("{} {}", category.emoji, category.name);
+    match category.color.parse::<Color>() {
+        Ok(color) => label.color(color),
+        Err(_) => label.normal(),
+    }
+}
+
+// Saves the tasks, asking before overwriting changes another command saved in the meantime
+fn save_tasks(manager: &mut TaskManager) -> Result<(), String> {
+    confirm_overwrite(manager)?;
+    manager.save().map_err(String::from)
+}
 
-            if let Some(current) = &task.current_time_entry {
-                println!("\nCurrent session:");
-                println!("Started: {}", current.start_time.format("%Y-%m-%d %H:%M:%S"));
-                println!("Running for: {:.2} hours", 
-                    (Local::now() - current.start_time).num_minutes() as f64 / 60.0);
+// Asks before letting the next save overwrite changes another command made to the tasks file
+fn confirm_overwrite(manager: &mut TaskManager) -> Result<(), String> {
+    match manager.check_unchanged() {
+        Err(TaskError::ChangedOnDisk(path)) => {
+            let overwrite = io::stdin().is_terminal()
+                && Confirm::new()
+                    .with_prompt(format!(
+                        "{} was changed by another command since it was loaded — overwrite those changes?",
+                        path.display()
+                    ))
+                    .default(false)
+                    .interact()
+                    .unwrap_or(false);
+            if !overwrite {
+                return Err(TaskError::ChangedOnDisk(path).into());
             }
+            manager.ignore_changes_on_disk();
+            Ok(())
+        }
+        result => result.map_err(String::from),
+    }
+}
 
-            println!("\nTotal time spent: {:.2} hours", total_duration.num_minutes() as f64 / 60.0);
-            println!("{}", "=".repeat(50).cyan());
-        } else {
-            println!("Task not found!");
-        }
-    }
-
-    // Checks for tasks that need notifications
-    fn check_notifications(&mut self) {
-        // First, collect all tasks that need notifications
-        let notifications: Vec<(String, String)> = self.tasks.iter()
-            .filter_map(|task| {
-                if let Some(due_date) = task.due_date {
-                    let now = Local::now();
-                    let time_until_due = due_date - now;
-
-                    // Check if task is due within 24 hours
-                    if time_until_due.num_hours() <= 24 && time_until_due.num_hours() >= 0 {
-                        let should_notify = match task.last_notification {
-                            Some(last) => (now - last).num_hours() >= 6,
-                            None => true,
-                        };
-
-                        if should_notify {
-                            let notification_text = format!(
-                                "Task '{}' is due {}!", 
-                                task.title,
-                                if time_until_due.num_hours() == 0 {
-                                    "now".to_string()
-                                } else {
-                                    format!("in {} hours", time_until_due.num_hours())
-                                }
-                            );
-                            return Some((task.id.to_string(), notification_text));
-                        }
-                    }
-                }
-                None
-            })
-            .collect();
+// Loads the tasks file, offering to move it aside if it is corrupt
+fn load_tasks(file_path: PathBuf) -> Result<TaskManager, String> {
+    match TaskManager::new(file_path) {
+        Err(TaskError::Corrupt { path, error }) => {
+            backup_corrupt_file(&path, &error)?;
+            Ok(TaskManager::new(path)?)
+        }
+        result => Ok(result?),
+    }
+}
+
+// Offers to move an unparseable tasks file aside so we can start fresh
+fn backup_corrupt_file(file_path: &Path, error: &serde_json::Error) -> Result<(), String> {
+    let mut backup_path = file_path.as_os_str().to_owned();
+    backup_path.push(".bak");
+    let backup_path = PathBuf::from(backup_path);
+    eprintln!(
+        "{} {} is corrupt: {}",
+        "Warning:".yellow().bold(),
+        file_path.display(),
+        error
+    );
+
+    let backup = Confirm::new()
+        .with_prompt(format!(
+            "Back it up to {} and start with an empty task list?",
+            backup_path.display()
+        ))
+        .default(false)
+        .interact()
+        .unwrap_or(false);
+
+    if !backup {
+        return Err(format!("Could not read {}: {}", file_path.display(), error));
+    }
+
+    fs::rename(file_path, &backup_path)
+        .map_err(|e| format!("Could not back up {}: {}", file_path.display(), e))?;
+    success!("{} Backed up broken tasks file to {}", CHECKMARK, backup_path.display());
+    Ok(())
+}
 
-        // Then, send notifications and update last_notification times
-        for (task_id, notification_text) in notifications {
-            match Notification::new()
-                .summary("Task Due Soon!")
-                .body(&notification_text)
-                .icon("calendar")
-                .show() 
-            {
-                Ok(_) => {
-                    if let Some(task) = self.tasks.iter_mut().find(|t| t.id.to_string() == task_id) {
-                        task.last_notification = Some(Local::now());
-                    }
-                },
-                Err(e) => println!("Failed to send notification: {}", e),
+// Restores the state from before the last command that changed tasks
+fn undo(manager: &mut TaskManager) -> Result<(), String> {
+    match manager.undo()? {
+        None => println!("Nothing to undo."),
+        Some(messages) => {
+            for message in messages {
+                success!("{} {}", CHECKMARK, message);
             }
         }
-        
-        // Save any updates to notification times
-        self.save();
     }
+    Ok(())
+}
+
+// Moves every Done task into the archive
+fn archive_done(manager: &mut TaskManager) -> Result<(), String> {
+    confirm_overwrite(manager)?;
+    match manager.archive_done()? {
+        0 => println!("No completed tasks to archive."),
+        count => success!("{} Archived {} completed tasks!", CHECKMARK, count),
+    }
+    Ok(())
+}
 
-    // Modified add_task method to handle categories after task creation
-    fn add_task(&mut self) {
-        // Get task title with interactive prompt
-        let title: String = Input::new()
-            .with_prompt(format!("{} Task title", SPARKLES))
+// Permanently deletes completed tasks, active and archived, after confirmation
+fn purge(manager: &mut TaskManager, before: Option<NaiveDate>, yes: bool) -> Result<(), String> {
+    let (active_count, archived_count) = manager.purgeable(before)?;
+    let total = active_count + archived_count;
+    if total == 0 {
+        println!("No completed tasks to purge.");
+        return Ok(());
+    }
+
+    if !yes {
+        let confirmed = Confirm::new()
+            .with_prompt(format!(
+                "Delete {} completed tasks ({} archived)? Only undo can bring them back.",
+                total, archived_count
+            ))
+            .default(false)
             .interact()
-            .unwrap();
+            .unwrap_or(false);
+        if !confirmed {
+            println!("Purge cancelled.");
+            return Ok(());
+        }
+    }
 
-        // Get optional task description
-        let description: String = Input::new()
-            .with_prompt(format!("{} Description (optional)", ROCKET))
-            .allow_empty(true)
+    confirm_overwrite(manager)?;
+    let purged = manager.purge(before)?;
+    success!("{} Purged {} completed tasks!", CHECKMARK, purged);
+    Ok(())
+}
+
+// Prints each planning problem with the tasks that have it and what to do about it
+fn print_lint(manager: &TaskManager, format: OutputFormat) {
+    let smells = lint(&manager.tasks, Local::now());
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&smells).unwrap());
+        return;
+    }
+    if smells.is_empty() {
+        println!("No problems found ✨");
+        return;
+    }
+    for smell in smells {
+        println!("{}{}: {}", WARNING, smell.problem.yellow().bold(), format_task_ids(&smell.ids));
+        println!("   {}", smell.suggestion);
+    }
+}
+
+// Writes a backup of the tasks, keeping only the newest VIBE_BACKUP_KEEP (10 by default,
+// 0 for all) in the backups folder
+fn backup(manager: &TaskManager, path: Option<PathBuf>) -> Result<(), String> {
+    let pruning = path.is_none();
+    let path = manager.backup(path)?;
+    success!("{} Backed up {} tasks to {}", CHECKMARK, manager.tasks.len(), path.display());
+
+    let keep = usize::try_from(env_number("VIBE_BACKUP_KEEP", 10)).unwrap_or(0);
+    if pruning && keep > 0 {
+        let pruned = manager.prune_backups(keep)?;
+        if pruned > 0 {
+            println!("Removed {} older {}.", pruned, if pruned == 1 { "backup" } else { "backups" });
+        }
+    }
+    Ok(())
+}
+
+// Replaces every task with those from a backup after confirmation; undo brings the old ones back
+fn restore(manager: &mut TaskManager, path: &Path, yes: bool) -> Result<(), String> {
+    let current = manager.tasks.len();
+    let restored = manager.restore(path)?;
+    if !yes {
+        let confirmed = Confirm::new()
+            .with_prompt(format!(
+                "Replace the {} current tasks with the {} tasks from {}?",
+                current,
+                restored,
+                path.display()
+            ))
+            .default(false)
             .interact()
-            .unwrap();
+            .unwrap_or(false);
+        if !confirmed {
+            println!("Restore cancelled.");
+            return Ok(());
+        }
+    }
 
-        // Priority selection using interactive menu
-        let priorities = vec!["Low", "Medium", "High", "Urgent"];
-        let priority_idx = Select::new()
-            .with_prompt(format!("{} Select priority", FIRE))
-            .items(&priorities)
-            .default(0)
+    save_tasks(manager)?;
+    success!("{} Restored {} tasks from {} (run undo to go back)", CHECKMARK, restored, path.display());
+    Ok(())
+}
+
+// Moves an archived task back into the active list
+fn unarchive(manager: &mut TaskManager, id: usize) -> Result<(), String> {
+    confirm_overwrite(manager)?;
+    let new_id = manager.unarchive(id)?;
+    if new_id == id {
+        success!("{} Task {} restored from the archive!", CHECKMARK, id);
+    } else {
+        success!("{} Task {} restored from the archive as task {} (its id was taken)", CHECKMARK, id, new_id);
+    }
+    Ok(())
+}
+
+// Adds a single category to a task by name
+fn assign_category(manager: &mut TaskManager, id: usize, name: &str) -> Result<(), String> {
+    let Some(category) = manager.resolve_category(name) else {
+        return Err(manager.unknown_category(name).into());
+    };
+    if !manager.add_category(id, category.clone())? {
+        println!("Task {} already has category {}!", id, category.name);
+        return Ok(());
+    }
+    save_tasks(manager)?;
+    success!("{} Task {} tagged {}!", CHECKMARK, id, colored_category(&category));
+    Ok(())
+}
+
+// Adds a category to every task whose title contains the text, asking first if that's many tasks
+fn categorize_matching(manager: &mut TaskManager, text: &str, name: &str, yes: bool) -> Result<(), String> {
+    let Some(category) = manager.resolve_category(name) else {
+        return Err(manager.unknown_category(name).into());
+    };
+    let ids: Vec<usize> = manager
+        .ids_with_title(text)
+        .into_iter()
+        .filter(|id| manager.find(*id).is_some_and(|t| !t.categories.iter().any(|c| c.name == category.name)))
+        .collect();
+    if ids.is_empty() {
+        println!("No tasks matching '{}' need category {}.", text, category.name);
+        return Ok(());
+    }
+
+    if !yes && ids.len() > BULK_CONFIRM_THRESHOLD {
+        let confirmed = Confirm::new()
+            .with_prompt(format!("Add category {} to {} tasks?", category.name, ids.len()))
+            .default(false)
             .interact()
-            .unwrap();
+            .unwrap_or(false);
+        if !confirmed {
+            println!("Categorizing cancelled.");
+            return Ok(());
+        }
+    }
 
-        // Convert selection index to Priority enum
-        let priority = match priority_idx {
-            0 => Priority::Low,
-            1 => Priority::Medium,
-            2 => Priority::High,
-            3 => Priority::Urgent,
-            _ => Priority::Medium,
-        };
+    for &id in &ids {
+        manager.add_category(id, category.clone())?;
+    }
+    save_tasks(manager)?;
+    success!("{} Tagged {} tasks {}!", CHECKMARK, ids.len(), colored_category(&category));
+    Ok(())
+}
 
-        // Get optional due date with specific format
-        let due_date: String = Input::new()
-            .with_prompt(format!("{} Due date (YYYY-MM-DD HH:MM, optional)", CALENDAR))
-            .allow_empty(true)
+// Lets the user pick a task's categories from the defined ones
+fn add_categories(manager: &mut TaskManager, id: usize) -> Result<(), String> {
+    let Some(task) = manager.find(id) else {
+        return Err(format!("Task {} not found!", id));
+    };
+    if manager.categories.is_empty() {
+        println!("No categories defined. Add one with category-add!");
+        return Ok(());
+    }
+
+    let category_names: Vec<String> = manager.categories
+        .iter()
+        .map(|c| format!("{} {}", c.emoji, c.name))
+        .collect();
+
+    // Pre-check the task's current categories so unchecking removes and checking adds
+    let mut checked: Vec<bool> = manager.categories
+        .iter()
+        .map(|c| task.categories.iter().any(|tc| tc.name == c.name))
+        .collect();
+
+    // Too many categories make the list unreadable, so ask again while over the cap (0 means no cap)
+    let max = max_categories();
+    let prompt = match max {
+        0 => format!("{} Select categories", TAG),
+        max => format!("{} Select categories (at most {})", TAG, max),
+    };
+    let selections = loop {
+        let selections = MultiSelect::new()
+            .with_prompt(&prompt)
+            .items(&category_names)
+            .defaults(&checked)
             .interact()
             .unwrap();
+        if max == 0 || selections.len() <= max {
+            break selections;
+        }
+        eprintln!("{} You can select at most {} categories.", "Warning:".yellow().bold(), max);
+        checked = (0..category_names.len()).map(|i| selections.contains(&i)).collect();
+    };
 
-        // Parse and validate due date if provided
-        let due_date = if !due_date.is_empty() {
-            match NaiveDateTime::parse_from_str(&due_date, "%Y-%m-%d %H:%M") {
-                Ok(dt) => Some(DateTime::from_naive_utc_and_offset(dt, Local::now().offset().clone())),
-                Err(_) => None,
-            }
-        } else {
-            None
-        };
+    let selected = selections.iter().map(|&i| manager.categories[i].clone()).collect();
+    manager.set_categories(id, selected)?;
+    save_tasks(manager)?;
+    success!("{} Categories updated!", CHECKMARK);
+    Ok(())
+}
 
-        // Create the task
-        let task_id = self.tasks.len() + 1;
-        let task = Task {
-            id: task_id,
-            title,
-            description: if description.is_empty() { None } else { Some(description) },
-            priority,
-            status: Status::Todo,
-            due_date,
-            created_at: Local::now(),
-            categories: Vec::new(),
-            time_entries: Vec::new(),
-            current_time_entry: None,
-            last_notification: None,
-        };
+// Defines a new category that can be assigned to tasks
+fn add_category_definition(manager: &mut TaskManager, name: String, color: String, emoji: String) -> Result<(), String> {
+    if !manager.add_category_definition(name.clone(), color, emoji.clone()) {
+        println!("Category '{}' already exists!", name);
+        return Ok(());
+    }
+    save_tasks(manager)?;
+    success!("{} Category {} {} added!", CHECKMARK, emoji, name);
+    Ok(())
+}
 
-        self.tasks.push(task);
-        self.save();
-        println!("{} Task added successfully!", CHECKMARK);
-        
-        // Add categories as a separate step
-        self.add_categories(task_id);
+// Prints the categories available for assignment
+fn list_category_definitions(manager: &TaskManager) {
+    if manager.categories.is_empty() {
+        println!("No categories defined. Add one with category-add!");
+        return;
     }
 
-    // Modified list_tasks method to show categories and time tracking
-    fn list_tasks(&self) {
-        if self.tasks.is_empty() {
-            println!("No tasks found. Add some tasks to get started! ✨");
-            return;
+    for category in &manager.categories {
+        println!("{} ({})", colored_category(category), category.color);
+    }
+}
+
+// Removes a category definition; tasks keep categories already assigned to them
+fn remove_category_definition(manager: &mut TaskManager, name: &str) -> Result<(), String> {
+    let category = manager.remove_category_definition(name)?;
+    save_tasks(manager)?;
+    success!("{} Category {} {} removed!", CHECKMARK, category.emoji, category.name);
+    Ok(())
+}
+
+// Starts time tracking for a task, warning if it is blocked or overdue
+fn start_time_tracking(manager: &mut TaskManager, id: usize, force: bool, switch: bool) -> Result<(), String> {
+    let stopped = manager.start_timer(id, force, switch)?;
+    timer_started(manager, id, stopped)
+}
+
+// Saves once a task's timer is running, reporting the timer it replaced (if any) and
+// warning if the task is blocked or overdue
+fn timer_started(manager: &mut TaskManager, id: usize, stopped: Option<usize>) -> Result<(), String> {
+    if let Some(busy) = stopped.and_then(|stopped| manager.find(stopped)) {
+        success!("{} Stopped time tracking for task #{} '{}'", CLOCK, busy.id, busy.title);
+    }
+    save_tasks(manager)?;
+    success!("{} Time tracking started!", CLOCK);
+
+    let Some(task) = manager.find(id) else {
+        return Ok(());
+    };
+    let blockers = manager.open_dependencies(task);
+    if !blockers.is_empty() {
+        eprintln!("{} Task #{} is still blocked by {}", "Warning:".yellow().bold(), id, format_task_ids(&blockers));
+    }
+    let now = Local::now();
+    if task.is_overdue(now) {
+        let overdue = now - task.due_date.unwrap();
+        println!("{}", format!("{}Heads up: this task was due {} ago.", WARNING, humanize(overdue)).yellow());
+    }
+    Ok(())
+}
+
+// Abandons a task's running session, e.g. one started by mistake, after confirmation
+fn clear_timer(manager: &mut TaskManager, id: usize, yes: bool) -> Result<(), String> {
+    let Some(task) = manager.find(id) else {
+        return Err(format!("Task {} not found!", id));
+    };
+    if task.current_time_entry.is_none() {
+        return Err(TaskError::NoActiveTimer(id).into());
+    }
+    if !yes {
+        let confirmed = Confirm::new()
+            .with_prompt(format!("Discard the running timer on task #{} '{}' without recording it?", id, task.title))
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+        if !confirmed {
+            println!("Timer kept running.");
+            return Ok(());
         }
+    }
 
-        for task in &self.tasks {
-            let status_str = match task.status {
-                Status::Todo => "TODO".red(),
-                Status::InProgress => "IN PROGRESS".yellow(),
-                Status::Done => "DONE".green(),
-            };
+    let elapsed = manager.clear_timer(id)?.num_seconds();
+    save_tasks(manager)?;
+    success!(
+        "{} Discarded running timer (was {:02}:{:02}:{:02})",
+        CLOCK,
+        elapsed / 3600, elapsed % 3600 / 60, elapsed % 60
+    );
+    Ok(())
+}
 
-            let priority_str = match task.priority {
-                Priority::Low => "LOW".blue(),
-                Priority::Medium => "MEDIUM".yellow(),
-                Priority::High => "HIGH".red(),
-                Priority::Urgent => "URGENT".red().bold(),
-            };
+// Stops time tracking for a task
+fn stop_time_tracking(manager: &mut TaskManager, id: usize) -> Result<(), String> {
+    manager.stop_timer(id)?;
+    save_tasks(manager)?;
+    success!("{} Time tracking stopped!", CLOCK);
+    Ok(())
+}
 
-            println!("\n{}", "=".repeat(50).cyan());
-            println!("Task #{}: {}", task.id, task.title.bold());
-            if let Some(desc) = &task.description {
-                println!("Description: {}", desc);
-            }
-            println!("Priority: {}", priority_str);
-            println!("Status: {}", status_str);
-            
-            // Display categories
-            if !task.categories.is_empty() {
-                print!("Categories: ");
-                for (i, category) in task.categories.iter().enumerate() {
-                    if i > 0 { print!(", "); }
-                    print!("{} {}", category.emoji, category.name);
-                }
-                println!();
-            }
+// Pauses time tracking, recording the segment so far and remembering to resume
+fn pause_time_tracking(manager: &mut TaskManager, id: usize) -> Result<(), String> {
+    manager.pause_timer(id)?;
+    save_tasks(manager)?;
+    success!("{} Time tracking paused!", CLOCK);
+    Ok(())
+}
 
-            // Display time tracking status
-            if let Some(current) = &task.current_time_entry {
-                println!("🔄 Currently tracking time (started: {})", 
-                    current.start_time.format("%H:%M:%S"));
-            }
-            if !task.time_entries.is_empty() {
-                let total_duration: Duration = task.time_entries
-                    .iter()
-                    .filter_map(|e| e.duration)
-                    .sum();
-                println!("⏱️ Total time: {:.2} hours", total_duration.num_minutes() as f64 / 60.0);
-            }
+// Resumes a paused task by starting a new time tracking segment
+fn resume_time_tracking(manager: &mut TaskManager, id: usize) -> Result<(), String> {
+    manager.resume_timer(id)?;
+    timer_started(manager, id, None)
+}
+
+// Lets the user fix or remove one of a task's recorded time entries
+fn edit_time_entry(manager: &mut TaskManager, id: usize) -> Result<(), String> {
+    let Some(task) = manager.find_mut(id) else {
+        return Err(format!("Task {} not found!", id));
+    };
+    if task.time_entries.is_empty() {
+        return Err(format!("No time entries recorded for task {}!", id));
+    }
+
+    let entry_names: Vec<String> = task.time_entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            format!(
+                "{}. {} → {} ({})",
+                i + 1,
+                entry.start_time.format("%Y-%m-%d %H:%M:%S"),
+                entry.end_time.map(|end| end.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default(),
+                format_tracked(entry.duration.unwrap_or_else(Duration::zero), false)
+            )
+        })
+        .collect();
+    let entry_idx = Select::new()
+        .with_prompt(format!("{} Select a session", CLOCK))
+        .items(&entry_names)
+        .default(0)
+        .interact()
+        .unwrap();
+
+    let actions = ["Adjust start/end times", "Delete session"];
+    let action_idx = Select::new()
+        .with_prompt("What should happen to this session?")
+        .items(&actions)
+        .default(0)
+        .interact()
+        .unwrap();
+
+    if action_idx == 1 {
+        task.time_entries.remove(entry_idx);
+        save_tasks(manager)?;
+        success!("{} Session {} deleted!", CHECKMARK, entry_idx + 1);
+        return Ok(());
+    }
+
+    let entry = &task.time_entries[entry_idx];
+    let start_time = prompt_timestamp("Start", entry.start_time);
+    let end_time = prompt_timestamp("End", entry.end_time.unwrap_or(entry.start_time));
+    task.adjust_time_entry(entry_idx, start_time, end_time)?;
+    save_tasks(manager)?;
+    success!("{} Session {} updated!", CHECKMARK, entry_idx + 1);
+    Ok(())
+}
+
+// Prints a time report for a task
+fn generate_time_report(
+    manager: &TaskManager,
+    id: usize,
+    hours: bool,
+    range: (Option<DateTime<Local>>, Option<DateTime<Local>>),
+    format: OutputFormat,
+) -> Result<(), String> {
+    let Some(task) = manager.find(id) else {
+        return Err(format!("Task {} not found!", id));
+    };
+    if format == OutputFormat::Json {
+        print_time_report_json(task, range);
+        return Ok(());
+    }
+
+    println!("\n{}", "=".repeat(50).cyan());
+    println!("Time Report for Task #{}: {}", task.id, task.title.bold());
+    let (since, until) = range;
+    // `until` is the start of the day after the last one included
+    let since_day = since.map(|d| d.format("%Y-%m-%d").to_string());
+    let until_day = until.map(|d| (d - Duration::days(1)).format("%Y-%m-%d").to_string());
+    match (since_day, until_day) {
+        (Some(since), Some(until)) => println!("Sessions started {} to {}", since, until),
+        (Some(since), None) => println!("Sessions started since {}", since),
+        (None, Some(until)) => println!("Sessions started up to {}", until),
+        (None, None) => {}
+    }
+
+    if task.time_entries.is_empty() && task.current_time_entry.is_none() {
+        println!("No time entries recorded for this task.");
+        return Ok(());
+    }
 
-            if let Some(due) = task.due_date {
-                println!("Due: {}", due.format("%Y-%m-%d %H:%M").to_string().magenta());
+    // Sessions keep their overall numbers so they match edit-time
+    for (i, entry) in task.time_entries.iter().enumerate().filter(|(_, e)| e.started_between(since, until)) {
+        if let Some(duration) = entry.duration {
+            println!("\nSession {}:", i + 1);
+            println!("Start: {}", entry.start_time.format("%Y-%m-%d %H:%M:%S"));
+            if let Some(end) = entry.end_time {
+                println!("End: {}", end.format("%Y-%m-%d %H:%M:%S"));
             }
-            println!("Created: {}", task.created_at.format("%Y-%m-%d %H:%M"));
+            println!("Duration: {}", format_tracked(duration, hours));
         }
-        println!("{}", "=".repeat(50).cyan());
     }
 
-    // Marks a specific task as complete
-    fn complete_task(&mut self, id: usize) {
-        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
-            task.status = Status::Done;
-            self.save();
-            println!("{} Task {} marked as complete!", CHECKMARK, id);
-        } else {
-            println!("Task not found!");
+    if task.paused {
+        println!("\nTime tracking is paused.");
+    }
+    if let Some(current) = task.current_time_entry.as_ref().filter(|c| c.started_between(since, until)) {
+        println!("\nCurrent session:");
+        println!("Started: {}", current.start_time.format("%Y-%m-%d %H:%M:%S"));
+        println!("Running for: {}", format_tracked(Local::now() - current.start_time, hours));
+        if let Some(warning) = forgotten_timer_warning(task, Local::now()) {
+            println!("{}", warning.yellow().bold());
         }
     }
 
-    // Updates the status of a specific task using interactive menu
-    fn update_status(&mut self, id: usize) {
-        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
-            let statuses = vec!["Todo", "In Progress", "Done"];
-            let status_idx = Select::new()
-                .with_prompt(format!("{} Select new status", ROCKET))
-                .items(&statuses)
-                .default(0)
-                .interact()
-                .unwrap();
-
-            task.status = match status_idx {
-                0 => Status::Todo,
-                1 => Status::InProgress,
-                2 => Status::Done,
-                _ => Status::Todo,
-            };
-            self.save();
-            println!("{} Task status updated!", CHECKMARK);
+    let tracked = task.tracked_between(since, until);
+    println!("\nTotal time spent: {}", format_tracked(tracked, hours));
+    if let (Some(estimate), Some(error)) = (task.estimated_minutes, task.estimate_error_percent()) {
+        // The estimate covers the whole task, so compare it with all tracked time
+        let spent = task.total_tracked().num_minutes();
+        let verdict = if error >= 0.0 {
+            format!("+{:.0}% over", error).red()
         } else {
-            println!("Task not found!");
+            format!("{:.0}% under", error).green()
+        };
+        let scope = if since.is_some() || until.is_some() { " in total (all dates)" } else { "" };
+        println!("Estimated {}m, spent {}m{}, {}", estimate, spent, scope, verdict);
+    }
+    if let Some((tracked, goal, percent)) = task.goal_progress(Local::now()) {
+        println!(
+            "This week: {} of {} goal {}",
+            format_minutes(tracked.num_minutes() as u32),
+            format_minutes(goal),
+            progress_bar(u8::try_from(percent).unwrap_or(u8::MAX))
+        );
+    }
+    if let Some(rate) = task.hourly_rate {
+        let hours = tracked.num_seconds() as f64 / 3600.0;
+        println!("Estimated cost: {:.2} ({:.2} hours at {:.2}/hour)", hours * rate, hours, rate);
+    }
+    println!("{}", "=".repeat(50).cyan());
+    Ok(())
+}
+
+// Sets the hourly billing rate for a task, or clears it when no rate is given
+fn set_rate(manager: &mut TaskManager, id: usize, rate: Option<f64>) -> Result<(), String> {
+    manager.set_rate(id, rate)?;
+    save_tasks(manager)?;
+    match rate {
+        Some(rate) => success!("{} Task {} billed at {:.2}/hour!", CHECKMARK, id, rate),
+        None => success!("{} Hourly rate cleared for task {}!", CHECKMARK, id),
+    }
+    Ok(())
+}
+
+// Sets or clears how long before its due date a task's priority starts rising
+fn set_escalation(manager: &mut TaskManager, id: usize, hours: Option<i64>) -> Result<(), String> {
+    manager.set_escalation(id, hours)?;
+    save_tasks(manager)?;
+    match hours {
+        Some(hours) => success!("{} Task {} escalates {} hours before it's due!", CHECKMARK, id, hours),
+        None => success!("{} Escalation cleared for task {}!", CHECKMARK, id),
+    }
+    Ok(())
+}
+
+// Raises priorities of tasks nearing their due date. Tasks opt in with set-escalation,
+// or all tasks do when VIBE_ESCALATE_HOURS is set.
+fn escalate(manager: &mut TaskManager, format: OutputFormat) -> Result<(), String> {
+    let default_hours = Some(env_number("VIBE_ESCALATE_HOURS", 0)).filter(|hours| *hours > 0);
+    let changes = manager.escalate_priorities(default_hours, Local::now());
+    if !changes.is_empty() {
+        save_tasks(manager)?;
+    }
+
+    if format == OutputFormat::Json {
+        let report: Vec<serde_json::Value> = changes
+            .iter()
+            .map(|(id, from, to)| serde_json::json!({ "id": id, "from": from, "to": to }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else if changes.is_empty() {
+        println!("No priorities to raise.");
+    } else {
+        for (id, from, to) in &changes {
+            success!("{} Task {} escalated: {} → {}", FIRE, id, colored_priority(from), colored_priority(to));
         }
     }
+    Ok(())
+}
 
-    // Removes a task from the list
-    fn delete_task(&mut self, id: usize) {
-        if let Some(pos) = self.tasks.iter().position(|t| t.id == id) {
-            self.tasks.remove(pos);
-            self.save();
-            println!("{} Task {} deleted!", CHECKMARK, id);
-        } else {
-            println!("Task not found!");
+// Sets or clears a task's weekly time goal
+fn set_goal(manager: &mut TaskManager, id: usize, minutes: Option<u32>) -> Result<(), String> {
+    manager.set_goal(id, minutes)?;
+    save_tasks(manager)?;
+    match minutes {
+        Some(minutes) => success!("{} Task {} goal: {} a week!", CHECKMARK, id, format_minutes(minutes)),
+        None => success!("{} Weekly goal cleared for task {}!", CHECKMARK, id),
+    }
+    Ok(())
+}
+
+// Prints billable hours and cost per task for a category and date range
+fn invoice(manager: &TaskManager, category: Option<&str>, since: Option<NaiveDate>, until: Option<NaiveDate>) {
+    let rows = manager.billable_hours(category, since, until);
+    if rows.is_empty() {
+        println!("No tracked time matches this invoice.");
+        return;
+    }
+
+    println!("\n{}", "=".repeat(50).cyan());
+    println!("{}", "Invoice".bold());
+    let mut total_hours = 0.0;
+    let mut total_cost = 0.0;
+    for (task, hours) in rows {
+        total_hours += hours;
+        match task.hourly_rate {
+            Some(rate) => {
+                total_cost += hours * rate;
+                println!("#{} {}: {:.2} hours × {:.2} = {:.2}", task.id, task.title, hours, rate, hours * rate);
+            }
+            None => println!("#{} {}: {:.2} hours (no rate)", task.id, task.title, hours),
         }
     }
+    println!("\nTotal: {:.2} hours, {:.2} billable", total_hours, total_cost);
+    println!("{}", "=".repeat(50).cyan());
 }
 
-fn main() {
-    let cli = Cli::parse();
-    let mut task_manager = TaskManager::new();
-
-    match cli.command {
-        Commands::Add => task_manager.add_task(),
-        Commands::List => task_manager.list_tasks(),
-        Commands::Complete { id } => task_manager.complete_task(id),
-        Commands::Status { id } => task_manager.update_status(id),
-        Commands::Delete { id } => task_manager.delete_task(id),
-        Commands::AddCategories { id } => task_manager.add_categories(id),
-        Commands::StartTime { id } => task_manager.start_time_tracking(id),
-        Commands::StopTime { id } => task_manager.stop_time_tracking(id),
-        Commands::TimeReport { id } => task_manager.generate_time_report(id),
-        Commands::CheckNotifications => task_manager.check_notifications(),
-    }
-}
\ No newline at end of file
+// Prints tracked hours per category, largest first
+fn print_category_time(manager: &TaskManager, since: Option<NaiveDate>) {
+    let rows = manager.category_hours(since);
+    if rows.is_empty() {
+        println!("No tracked time found.");
+        return;
+    }
+
+    println!("\n{}", "=".repeat(50).cyan());
+    println!("{}", "Time per Category".bold());
+    for (name, hours) in &rows {
+        let label = match manager.resolve_category(name) {
+            Some(category) => colored_category(&category),
+            None => name.normal(),
+        };
+        println!("{}: {:.2} hours", label, hours);
+    }
+    let since = since.map(start_of_day);
+    let total: Duration = manager.tasks.iter().map(|t| t.tracked_between(since, None)).sum();
+    println!("\nTotal: {:.2} hours", total.num_seconds() as f64 / 3600.0);
+    println!("Tasks in several categories count toward each of them.");
+    println!("{}", "=".repeat(50).cyan());
+}
+
+// Prints a task's time report as a JSON object with durations in seconds
+fn print_time_report_json(task: &Task, (since, until): (Option<DateTime<Local>>, Option<DateTime<Local>>)) {
+    let sessions: Vec<serde_json::Value> = task.time_entries
+        .iter()
+        .filter(|entry| entry.started_between(since, until))
+        .filter_map(|entry| {
+            entry.duration.map(|duration| serde_json::json!({
+                "start_time": entry.start_time,
+                "end_time": entry.end_time,
+                "duration_seconds": duration.num_seconds(),
+            }))
+        })
+        .collect();
+    let total_seconds = task.tracked_between(since, until).num_seconds();
+    let current_session = task.current_time_entry.as_ref().filter(|c| c.started_between(since, until)).map(|current| serde_json::json!({
+        "start_time": current.start_time,
+        "elapsed_seconds": (Local::now() - current.start_time).num_seconds(),
+    }));
+
+    let report = serde_json::json!({
+        "id": task.id,
+        "title": task.title,
+        "sessions": sessions,
+        "current_session": current_session,
+        "total_seconds": total_seconds,
+        "estimated_minutes": task.estimated_minutes,
+        "time_goal_minutes": task.time_goal_minutes,
+        "tracked_this_week_seconds": task.goal_progress(Local::now()).map(|(tracked, _, _)| tracked.num_seconds()),
+        "hourly_rate": task.hourly_rate,
+        "estimated_cost": task.hourly_rate.map(|rate| total_seconds as f64 / 3600.0 * rate),
+    });
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
+
+// What one round of reminders did: the id and text of each one sent, and an error for each
+// one that couldn't be
+type NotificationRound = (Vec<(usize, String)>, Vec<String>);
+
+// Sends a reminder for every task that is due soon
+fn check_notifications(manager: &mut TaskManager, via: NotifyVia) -> Result<NotificationRound, String> {
+    let lead_hours = env_number("VIBE_NOTIFY_LEAD_HOURS", 24);
+    let throttle_hours = env_number("VIBE_NOTIFY_THROTTLE_HOURS", 6);
+    let email = match via {
+        NotifyVia::Email => Some(EmailConfig::from_env()?),
+        NotifyVia::Desktop => None,
+    };
+
+    let mut sent_notifications = Vec::new();
+    let mut failures = Vec::new();
+    for (id, notification_text) in manager.due_notifications(lead_hours, throttle_hours, Local::now()) {
+        let priority = manager.find(id).map(|t| t.priority.clone()).unwrap_or(Priority::Medium);
+        let sent = match &email {
+            Some(email) => email.send(reminder_summary(&priority), &notification_text),
+            None => due_notification(&priority, &notification_text)
+                .show()
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+        };
+        match sent {
+            Ok(()) => {
+                if let Some(task) = manager.find_mut(id) {
+                    task.last_notification = Some(Local::now());
+                    sent_notifications.push((id, notification_text));
+                }
+            }
+            Err(e) => failures.push(format!("Failed to send the reminder for task {}: {}", id, e)),
+        }
+    }
+
+    // Only the notification file is written, so edits made while checking are never clobbered
+    manager.save_notifications()?;
+    Ok((sent_notifications, failures))
+}
+
+// Sends reminders once and reports what was sent, returning how many were. Reminders that
+// couldn't be sent make it fail, so cron can tell that apart from nothing being due.
+fn notify_due_tasks(manager: &mut TaskManager, via: NotifyVia, format: OutputFormat) -> Result<usize, String> {
+    let (sent, failures) = check_notifications(manager, via)?;
+    if format == OutputFormat::Json {
+        let report: Vec<serde_json::Value> = sent
+            .iter()
+            .map(|(id, text)| serde_json::json!({ "id": id, "text": text }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else if sent.is_empty() {
+        if failures.is_empty() {
+            println!("No reminders due.");
+        }
+    } else {
+        let noun = if sent.len() == 1 { "notification" } else { "notifications" };
+        success!("{} Sent {} {}", CLOCK, sent.len(), noun);
+    }
+
+    if failures.is_empty() { Ok(sent.len()) } else { Err(failures.join("\n")) }
+}
+
+// Checks notifications every `interval` seconds until Ctrl-C, reloading the
+// task file each time so edits from other commands are picked up
+fn watch(manager: &mut TaskManager, interval: u64, via: NotifyVia) -> Result<(), String> {
+    let running = stop_on_ctrlc()?;
+
+    success!("{} Watching for due tasks every {} seconds (Ctrl-C to stop)", CLOCK, interval);
+    while running.load(Ordering::SeqCst) {
+        *manager = load_tasks(manager.file_path().to_path_buf())?;
+        let (_, failures) = check_notifications(manager, via)?;
+        for failure in failures {
+            eprintln!("{} {}", "Warning:".yellow().bold(), failure);
+        }
+
+        // Sleep in short steps so Ctrl-C is handled promptly
+        for _ in 0..interval {
+            if !running.load(Ordering::SeqCst) {
+                break;
+            }
+            thread::sleep(time::Duration::from_secs(1));
+        }
+    }
+    println!("Stopping watch.");
+    Ok(())
+}
+
+// Alternates focus blocks (tracked against the task) and breaks until Ctrl-C
+fn pomodoro(manager: &mut TaskManager, id: usize, work: u64, rest: u64) -> Result<(), String> {
+    let running = stop_on_ctrlc()?;
+
+    while running.load(Ordering::SeqCst) {
+        start_time_tracking(manager, id, false, false)?;
+        if manager.find(id).is_none_or(|t| t.current_time_entry.is_none()) {
+            return Ok(());
+        }
+        let finished = countdown("🍅 Focus", work * 60, &running);
+        stop_time_tracking(manager, id)?;
+        if !finished {
+            break;
+        }
+
+        send_notification("Break time!", &format!("Take {} minutes off.", rest));
+        if !countdown("☕ Break", rest * 60, &running) {
+            break;
+        }
+        send_notification("Back to work!", &format!("Next {}-minute focus block starting.", work));
+    }
+    println!("Stopping pomodoro.");
+    Ok(())
+}
+
+// Adds a new task, prompting only for the fields not given as flags. With --no-prompt, or
+// when stdin isn't a terminal, nothing is asked: omitted optional fields stay empty.
+fn add_task(manager: &mut TaskManager, args: AddArgs, format: OutputFormat) -> Result<(), String> {
+    if args.stdin {
+        return add_task_from_stdin(manager, args.allow_dup, format);
+    }
+    let interactive = !args.no_prompt && io::stdin().is_terminal();
+    if !interactive && args.title.is_none() {
+        return Err("Give the task's title with --title when not prompting".to_string());
+    }
+    let categories = args.categories
+        .iter()
+        .map(|name| manager.resolve_category(name).ok_or_else(|| manager.unknown_category(name)))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Check the limit before prompting; the same category given twice only counts once
+    let mut names: Vec<&str> = categories.iter().map(|c| c.name.as_str()).collect();
+    names.sort();
+    names.dedup();
+    check_category_count(names.len())?;
+
+    // Get task title with interactive prompt
+    let title: String = match args.title {
+        Some(title) => title,
+        None => Input::new()
+            .with_prompt(format!("{} Task title", SPARKLES))
+            .interact()
+            .unwrap(),
+    };
+
+    // Get optional task description
+    let description: String = match args.description {
+        Some(description) => description,
+        None if interactive => Input::new()
+            .with_prompt(format!("{} Description (optional)", ROCKET))
+            .allow_empty(true)
+            .interact()
+            .unwrap(),
+        None => String::new(),
+    };
+
+    // Get optional assignee
+    let assignee: String = match args.assignee {
+        Some(assignee) => assignee,
+        None if interactive => Input::new()
+            .with_prompt(format!("{} Assignee (optional)", PERSON))
+            .allow_empty(true)
+            .interact()
+            .unwrap(),
+        None => String::new(),
+    };
+
+    // Priority selection using interactive menu
+    let priority = match args.priority {
+        Some(priority) => priority,
+        None if interactive => {
+            let default = default_priority();
+            let priority_idx = Select::new()
+                .with_prompt(format!("{} Select priority", FIRE))
+                .items(&Priority::ALL)
+                .default(Priority::ALL.iter().position(|p| *p == default).unwrap_or(0))
+                .interact()
+                .unwrap();
+            Priority::ALL[priority_idx].clone()
+        }
+        None => default_priority(),
+    };
+
+    // Get optional due date with specific format
+    let due_date = match args.due {
+        Some(due) => Some(due),
+        None if interactive => prompt_due_date(None),
+        None => None,
+    };
+
+    // Ask whether the task repeats
+    let recurrence = match args.repeat {
+        Some(recurrence) => Some(recurrence),
+        None if interactive => prompt_recurrence(),
+        None => None,
+    };
+
+    // Fill in a due date from the priority's configured offset, if there is one
+    let due_date = match (due_date, Config::load()?.due_offset(&priority)) {
+        (None, Some(offset)) => {
+            let due = Local::now() + offset;
+            success!("{} Auto-set due to {} based on {} priority", CALENDAR, due.format("%Y-%m-%d %H:%M"), priority);
+            Some(due)
+        }
+        (due_date, _) => due_date,
+    };
+
+    let mut task = Task::new(title, priority);
+    task.description = if description.is_empty() { None } else { Some(description) };
+    task.assignee = if assignee.is_empty() { None } else { Some(assignee) };
+    task.due_date = due_date;
+    task.recurrence = recurrence;
+    task.hourly_rate = args.rate;
+    task.notify_before = args.notify_before;
+    task.escalate_within = args.escalate_within;
+    task.estimated_minutes = args.estimate;
+    task.categories = categories;
+
+    if !args.allow_dup && manager.is_duplicate(&task) {
+        // Without someone to ask, refuse like add --stdin does
+        if !interactive {
+            return Err(format!(
+                "A task titled '{}' with the same due date already exists (use --allow-dup to add it anyway)",
+                task.title
+            ));
+        }
+        let add_anyway = Confirm::new()
+            .with_prompt(format!(
+                "A task titled '{}' with the same due date already exists — add anyway?",
+                task.title
+            ))
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+        if !add_anyway {
+            println!("Task not added.");
+            return Ok(());
+        }
+    }
+    let task_id = manager.add_task(task);
+    save_tasks(manager)?;
+    success!("{} Task added successfully!", CHECKMARK);
+
+    // Categories are an optional separate step, offered only when asked for or confirmed
+    let categorize = args.categorize
+        || (interactive
+            && args.categories.is_empty()
+            && Confirm::new()
+                .with_prompt(format!("{} Add categories now?", TAG))
+                .default(false)
+                .interact()
+                .unwrap_or(false));
+    if categorize {
+        add_categories(manager, task_id)?;
+    }
+    Ok(())
+}
+
+// Creates a task from a JSON spec on stdin, for scripts and integrations
+fn add_task_from_stdin(manager: &mut TaskManager, allow_dup: bool, format: OutputFormat) -> Result<(), String> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input).map_err(|e| format!("Could not read stdin: {}", e))?;
+    let spec: TaskSpec = serde_json::from_str(&input).map_err(|e| format!("Invalid task JSON: {}", e))?;
+    if spec.title.trim().is_empty() {
+        return Err("Invalid task JSON: title must not be empty".to_string());
+    }
+
+    let invalid = |e: String| format!("Invalid task JSON: {}", e);
+    let priority = match spec.priority {
+        Some(priority) => Priority::from_str(&priority).map_err(invalid)?,
+        None => default_priority(),
+    };
+    // Accept due dates as written by --format json as well as everything --due understands
+    let due_date = spec.due
+        .map(|due| match DateTime::parse_from_rfc3339(&due) {
+            Ok(due) => Ok(due.with_timezone(&Local)),
+            Err(_) => parse_due_date(&due),
+        })
+        .transpose()
+        .map_err(invalid)?;
+    let recurrence = spec.repeat.map(|repeat| parse_recurrence(&repeat)).transpose().map_err(invalid)?;
+    let categories = spec.categories
+        .iter()
+        .map(|name| manager.resolve_category(name).ok_or_else(|| manager.unknown_category(name)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut task = Task::new(spec.title, priority);
+    task.description = spec.description.filter(|d| !d.is_empty());
+    task.assignee = spec.assignee.filter(|a| !a.is_empty());
+    task.due_date = due_date;
+    task.recurrence = recurrence;
+    task.hourly_rate = spec.rate;
+    task.notify_before = spec.notify_before;
+    task.estimated_minutes = spec.estimate;
+    task.categories = categories;
+    task.normalize_categories();
+    check_category_count(task.categories.len())?;
+
+    if !allow_dup && manager.is_duplicate(&task) {
+        return Err(format!(
+            "A task titled '{}' with the same due date already exists (use --allow-dup to add it anyway)",
+            task.title
+        ));
+    }
+    let task_id = manager.add_task(task);
+    manager.tag_task(task_id, &spec.tags, false)?;
+    save_tasks(manager)?;
+    if format == OutputFormat::Json {
+        show_task(manager, task_id, format)?;
+    } else {
+        success!("{} Task #{} added successfully!", CHECKMARK, task_id);
+    }
+    Ok(())
+}
+
+// Asks how often a task should repeat, if at all
+fn prompt_recurrence() -> Option<Recurrence> {
+    let options = ["Never", "Daily", "Weekly", "Every N days"];
+    let repeat_idx = Select::new()
+        .with_prompt(format!("{} Repeat after completion?", CALENDAR))
+        .items(&options)
+        .default(0)
+        .interact()
+        .unwrap();
+
+    match repeat_idx {
+        1 => Some(Recurrence::Daily),
+        2 => Some(Recurrence::Weekly),
+        3 => {
+            let days: u32 = Input::new()
+                .with_prompt("Repeat every how many days?")
+                .validate_with(|days: &u32| if *days > 0 { Ok(()) } else { Err("Must be at least 1 day") })
+                .interact_text()
+                .unwrap();
+            Some(Recurrence::EveryDays(days))
+        }
+        _ => None,
+    }
+}
+
+// Records that task `id` can't be finished before task `on`, or removes that link
+fn set_dependency(manager: &mut TaskManager, id: usize, on: usize, remove: bool) -> Result<(), String> {
+    let changed = manager.set_dependency(id, on, remove)?;
+    match (changed, remove) {
+        (false, true) => println!("Task {} doesn't depend on task {}!", id, on),
+        (false, false) => println!("Task {} already depends on task {}!", id, on),
+        (true, true) => {
+            save_tasks(manager)?;
+            success!("{} Task {} no longer depends on task {}!", CHECKMARK, id, on);
+        }
+        (true, false) => {
+            save_tasks(manager)?;
+            success!("{} Task {} now depends on task {}!", CHECKMARK, id, on);
+        }
+    }
+    Ok(())
+}
+
+// Edits an existing task using the add prompts pre-filled with its current values
+fn edit_task(manager: &mut TaskManager, id: usize) -> Result<(), String> {
+    let Some(task) = manager.find_mut(id) else {
+        return Err(format!("Task {} not found!", id));
+    };
+    let title: String = Input::new()
+        .with_prompt(format!("{} Task title", SPARKLES))
+        .with_initial_text(task.title.clone())
+        .interact_text()
+        .unwrap();
+
+    let description: String = Input::new()
+        .with_prompt(format!("{} Description (optional)", ROCKET))
+        .with_initial_text(task.description.clone().unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()
+        .unwrap();
+
+    let assignee: String = Input::new()
+        .with_prompt(format!("{} Assignee (optional)", PERSON))
+        .with_initial_text(task.assignee.clone().unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()
+        .unwrap();
+
+    let current_priority = Priority::ALL.iter().position(|p| *p == task.priority).unwrap_or(0);
+    let priority_idx = Select::new()
+        .with_prompt(format!("{} Select priority", FIRE))
+        .items(&Priority::ALL)
+        .default(current_priority)
+        .interact()
+        .unwrap();
+
+    let due_date = prompt_due_date(task.due_date);
+
+    task.title = title;
+    task.description = if description.is_empty() { None } else { Some(description) };
+    task.assignee = if assignee.is_empty() { None } else { Some(assignee) };
+    // A priority picked by hand replaces any automatic escalation
+    if task.priority != Priority::ALL[priority_idx] {
+        task.priority = Priority::ALL[priority_idx].clone();
+        task.escalated_from = None;
+    }
+    task.due_date = due_date;
+
+    save_tasks(manager)?;
+    success!("{} Task {} updated!", CHECKMARK, id);
+    Ok(())
+}
+
+// Prints the tasks matching the list options, or them as JSON
+fn list_tasks(manager: &TaskManager, args: &ListArgs, format: OutputFormat) -> Result<(), String> {
+    let archived = if args.archived { manager.load_archive()? } else { Vec::new() };
+    let all_tasks = if args.archived { &archived } else { &manager.tasks };
+    let filter = args.filter.to_filter();
+    let mut tasks: Vec<&Task> = all_tasks.iter().filter(|t| filter.matches(t)).collect();
+    if args.overdue {
+        let now = Local::now();
+        tasks.retain(|t| t.is_overdue(now));
+        tasks.sort_by_key(|t| t.time_until_due(now));
+    }
+
+    // Stable sorts keep insertion order for ties
+    match args.sort {
+        Some(SortKey::Priority) => tasks.sort_by(|a, b| b.priority.cmp(&a.priority)),
+        Some(SortKey::Due) => tasks.sort_by_key(|t| (t.due_date.is_none(), t.due_date)),
+        Some(SortKey::Rank) => tasks.sort_by_key(|t| t.rank),
+        None => {}
+    }
+
+    // Summarizes everything that matched, including tasks cut off by --limit
+    let summary = list_summary(&tasks);
+
+    // --limit 0 means no limit
+    let hidden = match args.limit {
+        0 => 0,
+        limit => tasks.len().saturating_sub(limit),
+    };
+    tasks.truncate(tasks.len() - hidden);
+
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&tasks).unwrap());
+        return Ok(());
+    }
+
+    if all_tasks.is_empty() {
+        if args.archived {
+            println!("No archived tasks.");
+        } else {
+            println!("No tasks found. Add some tasks to get started! ✨");
+        }
+        return Ok(());
+    }
+
+    if tasks.is_empty() {
+        if args.overdue {
+            println!("Nothing overdue 🎉");
+        } else {
+            println!("No tasks match this filter.");
+        }
+        return Ok(());
+    }
+
+    println!("{}", summary);
+    if args.compact {
+        for task in tasks {
+            print_task_line(task, !args.absolute, task_tint(task, args.theme));
+        }
+    } else {
+        for task in tasks {
+            let tint = task_tint(task, args.theme);
+            print_task(task, &manager.open_dependencies(task), args.hours, !args.absolute, tint);
+        }
+        println!("{}", "=".repeat(50).cyan());
+    }
+    if hidden > 0 {
+        println!("… and {} more (use --limit 0 for all)", hidden);
+    }
+    Ok(())
+}
+
+// Writes the filtered tasks in the requested format to a file or stdout
+fn export_tasks(manager: &TaskManager, args: &ExportArgs) -> Result<(), String> {
+    let filter = args.filter.to_filter();
+    let tasks: Vec<&Task> = manager.tasks.iter().filter(|t| filter.matches(t)).collect();
+    let content = match args.kind {
+        ExportFormat::Markdown => render_markdown(&tasks),
+        ExportFormat::Ics => render_ics(&tasks, args.reminder),
+    };
+
+    match &args.output {
+        Some(path) => {
+            fs::write(path, content)
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+            success!("{} Exported {} tasks to {}", CHECKMARK, tasks.len(), path.display());
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+// One-line overview of the listed tasks, e.g. "12 tasks · 5 todo · 4 in progress · 3 done · 2 overdue"
+fn list_summary(tasks: &[&Task]) -> String {
+    let noun = if tasks.len() == 1 { "task" } else { "tasks" };
+    let mut parts = vec![format!("{} {}", tasks.len(), noun).bold().to_string()];
+    for (status, count) in count_by_status(tasks.iter().copied()) {
+        parts.push(status_style(&format!("{} {}", count, status.to_string().to_lowercase()), &status).to_string());
+    }
+    let overdue = tasks.iter().filter(|t| t.is_overdue(Local::now())).count();
+    let label = format!("{} overdue", overdue);
+    parts.push(if overdue > 0 { label.red().bold().to_string() } else { label });
+    parts.join(" · ")
+}
+
+// Prints a task on a single line, e.g. "#3 [HIGH] ○ Buy milk (due in 2h) 💼"
+fn print_task_line(task: &Task, relative: bool, tint: Option<Color>) {
+    let now = Local::now();
+    let due = task.due_date.map(|due| {
+        let when = if !relative {
+            format!("due {}", due.format("%Y-%m-%d %H:%M"))
+        } else if due < now {
+            format!("overdue {}", humanize(now - due))
+        } else {
+            format!("due in {}", humanize(due - now))
+        };
+        if task.is_overdue(now) {
+            format!(" ({})", when).red().bold()
+        } else {
+            format!(" ({})", when).magenta()
+        }
+    });
+    let emojis: String = task.categories.iter().map(|c| format!(" {}", c.emoji)).collect();
+    println!(
+        "{} {} {} {}{}{}",
+        format!("#{}", task.id).dimmed(),
+        priority_style(&format!("[{}]", task.priority.to_string().to_uppercase()), &task.priority),
+        status_style(status_symbol(&task.status), &task.status),
+        tint.map_or_else(|| task.title.bold(), |color| task.title.color(color).bold()),
+        due.map(|due| due.to_string()).unwrap_or_default(),
+        emojis
+    );
+}
+
+// Prints a single task's details, preceded by a separator line; the separator and title take
+// the tint if one is given
+fn print_task(task: &Task, blockers: &[usize], hours: bool, relative: bool, tint: Option<Color>) {
+    let status_str = colored_status(&task.status);
+    let priority_str = colored_priority(&task.priority);
+
+    println!("\n{}", "=".repeat(50).color(tint.unwrap_or(Color::Cyan)));
+    // A colored block makes priorities easy to scan; without colors it would just be noise
+    let marker = if colored::control::SHOULD_COLORIZE.should_colorize() {
+        format!("{} ", priority_style("█", &task.priority))
+    } else {
+        String::new()
+    };
+    let title = tint.map_or_else(|| task.title.bold(), |color| task.title.color(color).bold());
+    println!("{}Task #{}: {}", marker, task.id, title);
+    if let Some(desc) = &task.description {
+        println!("Description: {}", desc);
+    }
+    match &task.escalated_from {
+        Some(original) => println!("Priority: {} (escalated from {})", priority_str, original),
+        None => println!("Priority: {}", priority_str),
+    }
+    println!("Status: {}", status_str);
+    if let Some(assignee) = &task.assignee {
+        println!("{}Assignee: {}", PERSON, assignee.bold());
+    }
+    if !blockers.is_empty() {
+        println!("{}", format!("{}Blocked by {}", BLOCKED, format_task_ids(blockers)).red());
+    }
+    
+    // Display categories
+    if !task.categories.is_empty() {
+        print!("Categories: ");
+        for (i, category) in task.categories.iter().enumerate() {
+            if i > 0 { print!(", "); }
+            print!("{}", colored_category(category));
+        }
+        println!();
+    }
+    if !task.tags.is_empty() {
+        let chips: Vec<String> = task.tags.iter().map(|tag| format!("#{}", tag)).collect();
+        println!("Tags: {}", chips.join(" ").cyan());
+    }
+
+    // Display time tracking status
+    if let Some(current) = &task.current_time_entry {
+        let elapsed = (Local::now() - current.start_time).num_seconds();
+        println!("{}Currently tracking time (started: {}, tracking for {:02}:{:02}:{:02})",
+            TRACKING,
+            current.start_time.format("%H:%M:%S"),
+            elapsed / 3600, elapsed % 3600 / 60, elapsed % 60);
+        if let Some(warning) = forgotten_timer_warning(task, Local::now()) {
+            println!("{}", warning.yellow().bold());
+        }
+    } else if task.paused {
+        println!("{}Time tracking paused", PAUSED);
+    }
+    if !task.time_entries.is_empty() || task.current_time_entry.is_some() {
+        println!("{}Total time: {}", TIMER, format_tracked(task.total_tracked(), hours));
+    }
+    if let Some(estimate) = task.estimated_minutes {
+        println!("Estimate: {}m", estimate);
+    }
+    if let Some(goal) = task.time_goal_minutes {
+        println!("Weekly goal: {}", format_minutes(goal));
+    }
+
+    if let Some(recurrence) = &task.recurrence {
+        println!("Repeats: {}", recurrence);
+    }
+    if task.progress > 0 {
+        println!("Progress: {}", progress_bar(task.progress));
+    }
+
+    // Display subtasks as a checklist
+    if !task.subtasks.is_empty() {
+        let done = task.subtasks.iter().filter(|s| s.done).count();
+        println!("Subtasks: {}/{} complete", done, task.subtasks.len());
+        for (i, subtask) in task.subtasks.iter().enumerate() {
+            let checkbox = if subtask.done { "☑" } else { "☐" };
+            println!("  {} {}. {}", checkbox, i + 1, subtask.title);
+        }
+    }
+    if !task.notes.is_empty() {
+        println!("Notes:");
+        for note in &task.notes {
+            println!("  {} {}", note.created_at.format("%Y-%m-%d %H:%M").to_string().dimmed(), note.text);
+        }
+    }
+    let now = Local::now();
+    // Relative dates read as "in 3h" or "2d ago"
+    let show_date = |date: DateTime<Local>| {
+        if !relative {
+            date.format("%Y-%m-%d %H:%M").to_string()
+        } else if date > now {
+            format!("in {}", humanize(date - now))
+        } else {
+            format!("{} ago", humanize(now - date))
+        }
+    };
+    if let Some(due) = task.due_date {
+        let time_until_due = due - now;
+        if task.is_overdue(now) {
+            // The relative date would only repeat the "ago" part
+            let date = if relative { String::new() } else { format!("{} ", show_date(due)) };
+            println!("{}", format!("{}Due: {}OVERDUE ({} ago)", WARNING, date, humanize(time_until_due)).red().bold());
+        } else if task.status != Status::Done && time_until_due <= Duration::hours(24) {
+            println!("{}", format!("Due: {} (due soon)", show_date(due)).yellow());
+        } else {
+            println!("Due: {}", show_date(due).magenta());
+        }
+    }
+    println!("Created: {}", show_date(task.created_at));
+    if let Some(completed) = task.completed_at {
+        println!("Completed: {}", show_date(completed).green());
+    }
+}
+
+// Prints unfinished tasks due today or already overdue, ordered by due time
+fn print_today(manager: &TaskManager) {
+    let now = Local::now();
+    let today = now.date_naive();
+    let mut agenda: Vec<&Task> = manager.tasks
+        .iter()
+        .filter(|t| t.status != Status::Done)
+        .filter(|t| t.due_date.is_some_and(|due| due.date_naive() == today || t.is_overdue(now)))
+        .collect();
+    agenda.sort_by_key(|t| t.due_date);
+
+    if agenda.is_empty() {
+        println!("Nothing due today. Enjoy the vibes! ✨");
+        return;
+    }
+
+    println!("{}Agenda for {}", CALENDAR, today.format("%A, %Y-%m-%d").to_string().bold());
+    for task in agenda {
+        let due = task.due_date.unwrap();
+        let time = if due.date_naive() == today {
+            due.format("%H:%M").to_string()
+        } else {
+            due.format("%Y-%m-%d %H:%M").to_string()
+        };
+        let time = if task.is_overdue(now) { time.red().bold() } else { time.magenta() };
+        println!("  {}  #{} {} [{}]", time, task.id, task.title, colored_priority(&task.priority));
+    }
+}
+
+// Shows the single most pressing open task and offers to start tracking it
+fn suggest_next(manager: &mut TaskManager) -> Result<(), String> {
+    let Some(task) = manager.next_task(Local::now()) else {
+        println!("Nothing left to do. Enjoy the vibes! ✨");
+        return Ok(());
+    };
+    println!("{}Up next:", ROCKET);
+    print_task(task, &manager.open_dependencies(task), false, false, None);
+
+    let id = task.id;
+    if task.current_time_entry.is_none() {
+        let start = Confirm::new()
+            .with_prompt("Start tracking time?")
+            .default(false)
+            .interact()
+            .map_err(|e| e.to_string())?;
+        if start {
+            start_time_tracking(manager, id, false, false)?;
+        }
+    }
+    Ok(())
+}
+
+// Prints tracked time per day for one Monday-to-Sunday week (0 = this week, -1 = last week),
+// with a bar chart and per-task totals. Sessions count towards the day they started on.
+fn print_timesheet(manager: &TaskManager, week: i64) {
+    let today = Local::now().date_naive();
+    let monday = today - Duration::days(i64::from(today.weekday().num_days_from_monday())) + Duration::weeks(week);
+    let days: Vec<(NaiveDate, Duration)> = (0..7)
+        .map(|offset| {
+            let day = monday + Duration::days(offset);
+            let (start, end) = (start_of_day(day), start_of_day(day + Duration::days(1)));
+            let tracked = manager.tasks.iter().map(|t| t.tracked_between(Some(start), Some(end))).sum();
+            (day, tracked)
+        })
+        .collect();
+
+    println!("\n{}", "=".repeat(50).cyan());
+    println!("{}Timesheet for the week of {}", CALENDAR, monday.format("%Y-%m-%d").to_string().bold());
+    let longest = days.iter().map(|(_, d)| d.num_seconds()).max().unwrap_or(0).max(1);
+    for (day, tracked) in &days {
+        let bar = "█".repeat((tracked.num_seconds() * 30 / longest) as usize);
+        println!("{}  {:<30} {}", day.format("%a %m-%d"), bar.green(), format_tracked(*tracked, false));
+    }
+
+    let (start, end) = (start_of_day(monday), start_of_day(monday + Duration::weeks(1)));
+    let mut per_task: Vec<(&Task, Duration)> = manager.tasks
+        .iter()
+        .map(|t| (t, t.tracked_between(Some(start), Some(end))))
+        .filter(|(_, tracked)| *tracked > Duration::zero())
+        .collect();
+    per_task.sort_by_key(|(_, tracked)| std::cmp::Reverse(*tracked));
+
+    if !per_task.is_empty() {
+        println!("\n{}", "By task".cyan().bold());
+        for (task, tracked) in &per_task {
+            println!("#{} {}: {}", task.id, task.title, format_tracked(*tracked, false));
+        }
+    }
+    let total: Duration = days.iter().map(|(_, d)| *d).sum();
+    println!("\n{} {}", "Total:".cyan().bold(), format_tracked(total, false));
+    println!("{}", "=".repeat(50).cyan());
+}
+
+// Prints counts by status and priority, overdue tasks, this week's time, outstanding
+// estimated work (compared with the hours available, if given) and top categories
+fn print_stats(manager: &TaskManager, available_hours: Option<f64>) {
+    if manager.tasks.is_empty() {
+        println!("No tasks yet.");
+        return;
+    }
+
+    let now = Local::now();
+    println!("\n{}", "=".repeat(50).cyan());
+    println!("{}", "Task Stats".bold());
+
+    println!("\n{}", "By status".cyan().bold());
+    for (status, count) in count_by_status(&manager.tasks) {
+        println!("{}: {}", colored_status(&status), count);
+    }
+
+    println!("\n{}", "By priority".cyan().bold());
+    for priority in Priority::ALL.into_iter().rev() {
+        let count = manager.tasks.iter().filter(|t| t.priority == priority).count();
+        println!("{}: {}", colored_priority(&priority), count);
+    }
+
+    let overdue = manager.tasks.iter().filter(|t| t.is_overdue(now)).count();
+    println!("\n{} {}", "Overdue:".cyan().bold(), overdue);
+
+    let week_start = start_of_week(now.date_naive());
+    let tracked: Duration = manager.tasks.iter().map(|t| t.tracked_between(Some(week_start), None)).sum();
+    println!(
+        "{} {:.2} hours",
+        "Tracked this week:".cyan().bold(),
+        tracked.num_seconds() as f64 / 3600.0
+    );
+
+    let completion_times: Vec<Duration> = manager.tasks
+        .iter()
+        .filter_map(|t| t.completed_at.map(|completed| completed - t.created_at))
+        .collect();
+    if !completion_times.is_empty() {
+        let average = completion_times.iter().sum::<Duration>() / completion_times.len() as i32;
+        println!(
+            "{} {:.1} days",
+            "Average time to complete:".cyan().bold(),
+            average.num_hours() as f64 / 24.0
+        );
+    }
+
+    // Compare estimates with tracked time on finished tasks
+    let estimate_errors: Vec<f64> = manager.tasks
+        .iter()
+        .filter(|t| t.status == Status::Done)
+        .filter_map(|t| t.estimate_error_percent())
+        .collect();
+    if !estimate_errors.is_empty() {
+        let average = estimate_errors.iter().sum::<f64>() / estimate_errors.len() as f64;
+        println!(
+            "{} {:.0}% {} on average",
+            "Estimates:".cyan().bold(),
+            average.abs(),
+            if average >= 0.0 { "over" } else { "under" }
+        );
+    }
+
+    // Sum up estimates of everything still open
+    let open_estimates: Vec<(&Priority, u32)> = manager.tasks
+        .iter()
+        .filter(|t| t.status != Status::Done)
+        .filter_map(|t| t.estimated_minutes.map(|minutes| (&t.priority, minutes)))
+        .collect();
+    if !open_estimates.is_empty() {
+        let outstanding: u32 = open_estimates.iter().map(|(_, minutes)| minutes).sum();
+        println!(
+            "\n{} {} across {} tasks",
+            "Outstanding work:".cyan().bold(),
+            format_minutes(outstanding),
+            open_estimates.len()
+        );
+        for priority in Priority::ALL.into_iter().rev() {
+            let minutes: u32 = open_estimates.iter().filter(|(p, _)| **p == priority).map(|(_, m)| m).sum();
+            if minutes > 0 {
+                println!("{}: {}", colored_priority(&priority), format_minutes(minutes));
+            }
+        }
+        if let Some(hours) = available_hours {
+            let spare = (hours * 60.0).round() as i64 - i64::from(outstanding);
+            if spare < 0 {
+                println!("{}", format!("Over-committed by {}", format_minutes(spare.unsigned_abs() as u32)).red().bold());
+            } else {
+                println!("{}", format!("{} to spare", format_minutes(spare as u32)).green());
+            }
+        }
+    }
+
+    // Count tasks per category, keeping the first-seen order for ties
+    let mut category_counts: Vec<(&Category, usize)> = Vec::new();
+    for category in manager.tasks.iter().flat_map(|t| &t.categories) {
+        match category_counts.iter_mut().find(|(c, _)| c.name == category.name) {
+            Some((_, count)) => *count += 1,
+            None => category_counts.push((category, 1)),
+        }
+    }
+    category_counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    if !category_counts.is_empty() {
+        println!("\n{}", "Top categories".cyan().bold());
+        for (category, count) in category_counts.iter().take(3) {
+            println!("{}: {}", colored_category(category), count);
+        }
+    }
+    println!("{}", "=".repeat(50).cyan());
+}
+
+// Prints every task whose title, description or category names contain the query
+fn search_tasks(manager: &TaskManager, query: &str) {
+    let matches = manager.search(query);
+    if matches.is_empty() {
+        println!("No tasks match '{}'", query);
+        return;
+    }
+
+    for task in matches {
+        print_task(task, &manager.open_dependencies(task), false, false, None);
+    }
+    println!("{}", "=".repeat(50).cyan());
+}
+
+// Prints everything recorded for one task, including each time tracking session
+fn show_task(manager: &TaskManager, id: usize, format: OutputFormat) -> Result<(), String> {
+    let Some(task) = manager.find(id) else {
+        return Err(format!("Task {} not found!", id));
+    };
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(task).unwrap());
+        return Ok(());
+    }
+
+    print_task(task, &manager.open_dependencies(task), false, false, None);
+    if !task.time_entries.is_empty() {
+        println!("Sessions:");
+        for (i, entry) in task.time_entries.iter().enumerate() {
+            println!(
+                "  {}. {} → {} ({})",
+                i + 1,
+                entry.start_time.format("%Y-%m-%d %H:%M"),
+                entry.end_time.map(|end| end.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default(),
+                format_tracked(entry.duration.unwrap_or_else(Duration::zero), false)
+            );
+        }
+    }
+    println!("{}", "=".repeat(50).cyan());
+    Ok(())
+}
+
+// Sets a task's percent complete, offering to finish it at 100%
+fn set_progress(manager: &mut TaskManager, id: usize, percent: i64) -> Result<(), String> {
+    let task = manager.set_progress(id, percent)?;
+    let finished = task.progress == 100 && task.status != Status::Done;
+    success!("{} Task {} progress: {}", CHECKMARK, id, progress_bar(task.progress));
+    save_tasks(manager)?;
+
+    if finished {
+        let complete = Confirm::new()
+            .with_prompt(format!("Task {} is at 100% — mark it as Done?", id))
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+        if complete {
+            complete_tasks(manager, &[id], false, None)?;
+        }
+    }
+    Ok(())
+}
+
+// Moves a task one place up or down the ranked order, or to a given rank
+fn move_task(manager: &mut TaskManager, id: usize, up: bool, down: bool, to: Option<u64>) -> Result<(), String> {
+    let Some(task) = manager.find(id) else {
+        return Err(format!("Task {} not found!", id));
+    };
+    let target = match to {
+        Some(rank) => rank as usize,
+        None if up => task.rank.saturating_sub(1),
+        None if down => task.rank + 1,
+        None => task.rank,
+    };
+    let from = task.rank;
+    let rank = manager.move_task(id, target)?;
+    if rank == from {
+        println!("Task #{} is already at rank {}.", id, rank);
+        return Ok(());
+    }
+    save_tasks(manager)?;
+    success!("{} Task {} moved to rank {} of {}", CHECKMARK, id, rank, manager.tasks.len());
+    Ok(())
+}
+
+// Copies a task's title, description, priority, categories and due date into a fresh Todo task
+fn duplicate_task(manager: &mut TaskManager, id: usize) -> Result<(), String> {
+    let new_id = manager.duplicate_task(id)?;
+    save_tasks(manager)?;
+    success!("{} Task {} duplicated as task #{}!", CHECKMARK, id, new_id);
+    Ok(())
+}
+
+// Remembers a task's reusable fields under a name for template-apply
+fn save_template(manager: &TaskManager, id: usize, name: &str) -> Result<(), String> {
+    let Some(task) = manager.find(id) else {
+        return Err(format!("Task {} not found!", id));
+    };
+    let mut templates = Templates::load()?;
+    let replaced = templates.insert(name, task);
+    templates.save()?;
+    let verb = if replaced { "updated" } else { "saved" };
+    success!("{} Template '{}' {} from task {}!", CHECKMARK, name, verb, id);
+    Ok(())
+}
+
+// Creates a new Todo task from a saved template
+fn apply_template(manager: &mut TaskManager, name: &str, due: Option<DateTime<Local>>) -> Result<(), String> {
+    let mut task = Templates::load()?.instantiate(name)?;
+    task.due_date = due;
+    let id = manager.add_task(task);
+    save_tasks(manager)?;
+    success!("{} Task #{} created from template '{}'!", CHECKMARK, id, name);
+    Ok(())
+}
+
+// Lists the saved templates with the task fields each one fills in
+fn print_templates() -> Result<(), String> {
+    let templates = Templates::load()?.templates;
+    if templates.is_empty() {
+        println!("No templates saved yet. Use template-save <id> <name> to create one.");
+        return Ok(());
+    }
+    for (name, task) in &templates {
+        let mut details = vec![format!("{}", task.priority)];
+        if !task.subtasks.is_empty() {
+            details.push(format!("{} subtasks", task.subtasks.len()));
+        }
+        if !task.categories.is_empty() {
+            let names: Vec<&str> = task.categories.iter().map(|c| c.name.as_str()).collect();
+            details.push(names.join(", "));
+        }
+        println!("{} — {} ({})", name.bold(), task.title, details.join("; "));
+    }
+    Ok(())
+}
+
+// Adds free-form tags to a task, or removes them
+fn tag_task(manager: &mut TaskManager, id: usize, tags: &[String], remove: bool) -> Result<(), String> {
+    manager.tag_task(id, tags, remove)?;
+    save_tasks(manager)?;
+    success!("{} Tags updated for task {}!", CHECKMARK, id);
+    Ok(())
+}
+
+// Pushes a task's due date back (or sets it from now) and lets its reminder fire again
+fn snooze_task(manager: &mut TaskManager, id: usize, duration: Duration) -> Result<(), String> {
+    let due = manager.snooze_task(id, duration)?;
+    save_tasks(manager)?;
+    success!("{} Task {} snoozed until {}", CALENDAR, id, due.format("%Y-%m-%d %H:%M"));
+    Ok(())
+}
+
+// Appends a timestamped note to a task's progress log, prompting for the text if not given
+fn add_note(manager: &mut TaskManager, id: usize, text: Option<String>) -> Result<(), String> {
+    if manager.find(id).is_none() {
+        return Err(format!("Task {} not found!", id));
+    }
+    let text = match text {
+        Some(text) => text,
+        None => Input::new()
+            .with_prompt(format!("{} Note", SPARKLES))
+            .interact_text()
+            .unwrap(),
+    };
+
+    manager.add_note(id, text)?;
+    save_tasks(manager)?;
+    success!("{} Note added to task {}!", CHECKMARK, id);
+    Ok(())
+}
+
+// Appends a checklist item to a task, prompting for the title if not given
+fn add_subtask(manager: &mut TaskManager, id: usize, title: Option<String>) -> Result<(), String> {
+    if manager.find(id).is_none() {
+        return Err(format!("Task {} not found!", id));
+    }
+    let title = match title {
+        Some(title) => title,
+        None => Input::new()
+            .with_prompt(format!("{} Subtask title", SPARKLES))
+            .interact_text()
+            .unwrap(),
+    };
+
+    let index = manager.add_subtask(id, title)?;
+    save_tasks(manager)?;
+    success!("{} Subtask {} added to task {}!", CHECKMARK, index, id);
+    Ok(())
+}
+
+// Flips a checklist item between done and not done (index is 1-based)
+fn toggle_subtask(manager: &mut TaskManager, id: usize, index: usize) -> Result<(), String> {
+    let task = manager.toggle_subtask(id, index)?;
+    let subtask = &task.subtasks[index - 1];
+    let checkbox = if subtask.done { "☑" } else { "☐" };
+    println!("{} {}", checkbox, subtask.title);
+
+    // Suggest completing the parent once its whole checklist is done
+    let all_done = task.subtasks.iter().all(|s| s.done);
+    let parent_open = task.status != Status::Done;
+    save_tasks(manager)?;
+
+    if all_done && parent_open {
+        let complete = Confirm::new()
+            .with_prompt(format!("All subtasks complete — mark task {} as Done?", id))
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+        if complete {
+            complete_tasks(manager, &[id], false, None)?;
+        }
+    }
+    Ok(())
+}
+
+// Marks the given tasks as complete, saving once and reporting each id, and adds the
+// closing note to each one if given.
+// Ids that can't be completed are skipped and reported together as the error.
+fn complete_tasks(manager: &mut TaskManager, ids: &[usize], force: bool, note: Option<&str>) -> Result<(), String> {
+    let mut messages = Vec::new();
+    let mut errors = Vec::new();
+    for &id in ids {
+        let completion = match manager.complete_task(id, force) {
+            Ok(completion) => completion,
+            Err(e) => {
+                errors.push(e.to_string());
+                continue;
+            }
+        };
+        if let Some(note) = note {
+            manager.add_note(id, note.to_string())?;
+        }
+        if completion.stopped_timer {
+            messages.push(format!("{} Time tracking stopped!", CLOCK));
+        }
+        messages.push(format!("{} Task {} marked as complete!", CHECKMARK, id));
+        if let Some(next) = completion.next_occurrence.and_then(|next_id| manager.find(next_id)) {
+            let due = next.due_date.map(|d| d.format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default();
+            messages.push(format!("{} Next occurrence created as task #{} (due {})", CALENDAR, next.id, due));
+        }
+    }
+    if !messages.is_empty() {
+        save_tasks(manager)?;
+    }
+    for message in messages {
+        success!("{}", message);
+    }
+    if errors.is_empty() { Ok(()) } else { Err(errors.join("\n")) }
+}
+
+// Moves a Done task back to In Progress (or Todo)
+fn reopen_task(manager: &mut TaskManager, id: usize, todo: bool) -> Result<(), String> {
+    if !manager.reopen_task(id, todo)? {
+        println!("Task #{} isn't completed.", id);
+        return Ok(());
+    }
+    save_tasks(manager)?;
+    success!("{} Task {} reopened!", CHECKMARK, id);
+
+    let next_occurrence = manager.find(id).and_then(|t| t.next_occurrence);
+    if let Some(next_id) = next_occurrence.filter(|next_id| manager.find(*next_id).is_some()) {
+        println!(
+            "{} Task #{} already created its next occurrence as task #{}; delete it if it's no longer needed.",
+            "Warning:".yellow().bold(),
+            id,
+            next_id
+        );
+    }
+    Ok(())
+}
+
+// Updates the status of the given tasks using a single interactive menu
+fn update_status(manager: &mut TaskManager, ids: &[usize]) -> Result<(), String> {
+    let (found, missing): (Vec<usize>, Vec<usize>) = ids.iter().partition(|id| manager.find(**id).is_some());
+    if !found.is_empty() {
+        let status_idx = Select::new()
+            .with_prompt(format!("{} Select new status", ROCKET))
+            .items(&Status::ALL)
+            .default(0)
+            .interact()
+            .unwrap();
+        manager.set_status(&found, &Status::ALL[status_idx]);
+        save_tasks(manager)?;
+        for id in &found {
+            success!("{} Task {} status updated!", CHECKMARK, id);
+        }
+    }
+
+    // Ids that don't exist are reported after the others are updated
+    let missing: Vec<String> = missing.into_iter().map(|id| TaskError::NotFound(id).to_string()).collect();
+    if missing.is_empty() { Ok(()) } else { Err(missing.join("\n")) }
+}
+
+// Sets the status of all tasks matching a category and/or priority, after confirmation
+fn bulk_set_status(
+    manager: &mut TaskManager,
+    status: Status,
+    category: Option<&str>,
+    priority: Option<&Priority>,
+    yes: bool,
+) -> Result<(), String> {
+    let ids = manager.ids_matching(category, priority);
+    if ids.is_empty() {
+        println!("No tasks match this filter.");
+        return Ok(());
+    }
+
+    if !yes {
+        let confirmed = Confirm::new()
+            .with_prompt(format!("Set {} tasks to {}?", ids.len(), status))
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+        if !confirmed {
+            println!("Status update cancelled.");
+            return Ok(());
+        }
+    }
+
+    manager.set_status(&ids, &status);
+    save_tasks(manager)?;
+    success!("{} Updated {} tasks to {}!", CHECKMARK, ids.len(), status);
+    Ok(())
+}
+
+// Removes the given tasks from the list after confirmation, saving once
+fn delete_tasks(manager: &mut TaskManager, ids: &[usize], yes: bool) -> Result<(), String> {
+    let titles: Vec<String> = manager.tasks
+        .iter()
+        .filter(|t| ids.contains(&t.id))
+        .map(|t| format!("#{} '{}'", t.id, t.title))
+        .collect();
+    if !yes && !titles.is_empty() {
+        let prompt = match titles.as_slice() {
+            [title] => format!("Delete task {}?", title),
+            _ => format!("Delete {} tasks: {}?", titles.len(), titles.join(", ")),
+        };
+        let confirmed = Confirm::new()
+            .with_prompt(prompt)
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+        if !confirmed {
+            println!("Deletion cancelled.");
+            return Ok(());
+        }
+    }
+
+    let mut messages = Vec::new();
+    let mut missing = Vec::new();
+    for &id in ids {
+        match manager.delete_task(id) {
+            Ok(_) => messages.push(format!("{} Task {} deleted!", CHECKMARK, id)),
+            Err(e) => missing.push(e.to_string()),
+        }
+    }
+
+    if !messages.is_empty() {
+        save_tasks(manager)?;
+    }
+    for message in messages {
+        success!("{}", message);
+    }
+    if missing.is_empty() { Ok(()) } else { Err(missing.join("\n")) }
+}
+
+fn main() {
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // A --list given on the command line beats a VIBE_TASKS_FILE from the environment
+    if matches.value_source("file") == Some(ValueSource::EnvVariable)
+        && matches.value_source("list") == Some(ValueSource::CommandLine)
+    {
+        cli.file = None;
+    }
+
+    // Plain output when asked for, or when piped into another program or a file
+    if cli.no_color || std::env::var_os("NO_COLOR").is_some() || !io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+    QUIET.store(cli.quiet, Ordering::Relaxed);
+
+    // Completions, list management, listing templates and settings don't need the tasks file
+    match &cli.command {
+        Commands::Completions { shell } => {
+            clap_complete::generate(*shell, &mut Cli::command(), "vibe_tasks", &mut std::io::stdout());
+            return;
+        }
+        Commands::Lists => {
+            print_lists().unwrap_or_else(|e| exit_with_error(&e));
+            return;
+        }
+        Commands::SwitchList { name } => {
+            switch_list(name).unwrap_or_else(|e| exit_with_error(&e));
+            return;
+        }
+        Commands::TemplateList => {
+            print_templates().unwrap_or_else(|e| exit_with_error(&e));
+            return;
+        }
+        Commands::DueOffset { priority, offset, clear } => {
+            due_offset(priority.clone(), offset.as_deref(), *clear).unwrap_or_else(|e| exit_with_error(&e));
+            return;
+        }
+        _ => {}
+    }
+    let mut task_manager = TaskManager::resolve_file_path(cli.file, cli.list.as_deref())
+        .map_err(String::from)
+        .and_then(load_tasks)
+        .unwrap_or_else(|e| exit_with_error(&e));
+
+    let result = match cli.command {
+        Commands::Add(args) => add_task(&mut task_manager, args, cli.format),
+        Commands::List(args) => list_tasks(&task_manager, &args, cli.format),
+        Commands::Complete { ids, force, note } if ids.is_empty() => {
+            pick_task(&task_manager, "Complete which task?", |t| t.status != Status::Done)
+                .and_then(|id| complete_tasks(&mut task_manager, &[id], force, note.as_deref()))
+        }
+        Commands::Complete { ids, force, note } => with_selected_ids(&mut task_manager, &ids, |manager, ids| {
+            complete_tasks(manager, ids, force, note.as_deref())
+        }),
+        Commands::Reopen { id, todo } => reopen_task(&mut task_manager, id, todo),
+        Commands::Status { ids } => with_selected_ids(&mut task_manager, &ids, update_status),
+        Commands::SetStatus { status, category, priority, yes } => {
+            bulk_set_status(&mut task_manager, status, category.as_deref(), priority.as_ref(), yes)
+        }
+        Commands::Edit { id } => edit_task(&mut task_manager, id),
+        Commands::Delete { ids, yes } if ids.is_empty() => {
+            pick_task(&task_manager, "Delete which task?", |_| true).and_then(|id| delete_tasks(&mut task_manager, &[id], yes))
+        }
+        Commands::Delete { ids, yes } => {
+            with_selected_ids(&mut task_manager, &ids, |manager, ids| delete_tasks(manager, ids, yes))
+        }
+        Commands::AddCategories { id } => add_categories(&mut task_manager, id),
+        Commands::CategoryAdd { name, color, emoji } => {
+            add_category_definition(&mut task_manager, name, color, emoji)
+        }
+        Commands::CategoryList => {
+            list_category_definitions(&task_manager);
+            Ok(())
+        }
+        Commands::CategoryRemove { name } => remove_category_definition(&mut task_manager, &name),
+        Commands::CategoryAssign { id, name } => assign_category(&mut task_manager, id, &name),
+        Commands::Categorize { text, category, yes } => categorize_matching(&mut task_manager, &text, &category, yes),
+        Commands::Tag { id, tags, remove } => tag_task(&mut task_manager, id, &tags, remove),
+        Commands::StartTime { id, force, switch } => {
+            let id = match id {
+                Some(selector) => task_manager.resolve_task(&selector).map(|task| task.id).map_err(String::from),
+                None => pick_task(&task_manager, "Start tracking which task?", |t| {
+                    t.status != Status::Done && t.current_time_entry.is_none()
+                }),
+            };
+            id.and_then(|id| start_time_tracking(&mut task_manager, id, force, switch))
+        }
+        Commands::StopTime { id } => stop_time_tracking(&mut task_manager, id),
+        Commands::ClearTimer { id, yes } => clear_timer(&mut task_manager, id, yes),
+        Commands::PauseTime { id } => pause_time_tracking(&mut task_manager, id),
+        Commands::ResumeTime { id } => resume_time_tracking(&mut task_manager, id),
+        Commands::EditTime { id } => edit_time_entry(&mut task_manager, id),
+        Commands::TimeReport { id, hours, since, until } => {
+            generate_time_report(&task_manager, id, hours, date_range(since, until), cli.format)
+        }
+        Commands::AddSubtask { id, title } => add_subtask(&mut task_manager, id, title),
+        Commands::ToggleSubtask { id, index } => toggle_subtask(&mut task_manager, id, index),
+        Commands::DependsOn { id, on, remove } => set_dependency(&mut task_manager, id, on, remove),
+        Commands::Progress { id, percent } => set_progress(&mut task_manager, id, percent),
+        Commands::Pomodoro { id, work, rest } => pomodoro(&mut task_manager, id, work, rest),
+        Commands::Move { id, up, down, to } => move_task(&mut task_manager, id, up, down, to),
+        Commands::Duplicate { id } => duplicate_task(&mut task_manager, id),
+        Commands::TemplateSave { id, name } => save_template(&task_manager, id, &name),
+        Commands::TemplateApply { name, due } => apply_template(&mut task_manager, &name, due),
+        Commands::Snooze { id, duration } => snooze_task(&mut task_manager, id, duration),
+        Commands::Show { id } => show_task(&task_manager, id, cli.format),
+        Commands::Note { id, text } => add_note(&mut task_manager, id, text),
+        Commands::Export(args) => export_tasks(&task_manager, &args),
+        Commands::SetRate { id, rate } => set_rate(&mut task_manager, id, rate),
+        Commands::SetGoal { id, minutes } => set_goal(&mut task_manager, id, minutes),
+        Commands::SetEscalation { id, hours } => set_escalation(&mut task_manager, id, hours),
+        Commands::Escalate => escalate(&mut task_manager, cli.format),
+        Commands::Invoice { category, since, until } => {
+            invoice(&task_manager, category.as_deref(), since, until);
+            Ok(())
+        }
+        Commands::CategoryTime { since } => {
+            print_category_time(&task_manager, since);
+            Ok(())
+        }
+        Commands::Today => {
+            print_today(&task_manager);
+            Ok(())
+        }
+        Commands::Next => suggest_next(&mut task_manager),
+        Commands::Timesheet { week } => {
+            print_timesheet(&task_manager, week);
+            Ok(())
+        }
+        Commands::Lint => {
+            print_lint(&task_manager, cli.format);
+            Ok(())
+        }
+        Commands::Stats { available } => {
+            print_stats(&task_manager, available);
+            Ok(())
+        }
+        Commands::Archive => archive_done(&mut task_manager),
+        Commands::Unarchive { id } => unarchive(&mut task_manager, id),
+        Commands::Purge { before, yes } => purge(&mut task_manager, before, yes),
+        Commands::Undo => undo(&mut task_manager),
+        Commands::Backup { path } => backup(&task_manager, path),
+        Commands::Restore { path, yes } => restore(&mut task_manager, &path, yes),
+        Commands::Search { query } => {
+            search_tasks(&task_manager, &query);
+            Ok(())
+        }
+        // Exit with NOTIFIED_EXIT_CODE when reminders went out, so cron wrappers can react
+        Commands::CheckNotifications { notify } => match notify_due_tasks(&mut task_manager, notify, cli.format) {
+            Ok(sent) if sent > 0 => process::exit(NOTIFIED_EXIT_CODE),
+            result => result.map(|_| ()),
+        },
+        Commands::Watch { interval, notify } => watch(&mut task_manager, interval, notify),
+        Commands::Completions { .. } | Commands::Lists | Commands::SwitchList { .. }
+        | Commands::TemplateList
+        | Commands::DueOffset { .. } => {
+            unreachable!("handled before loading tasks")
+        }
+    };
+
+    if let Err(e) = result {
+        exit_with_error(&e);
+    }
+}
+
+// Prints an error message and exits with a non-zero status
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("{} {}", "Error:".red().bold(), message);
+    process::exit(1);
+}